@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Budget of `config.budgets` an alert is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetKind {
+    /// Percent of the 5-hour limit used in the current window
+    WindowPercent,
+    /// Estimated API cost of the current day
+    DayCost,
+    /// Output tokens of the current week
+    WeekTokens,
+}
+
+impl BudgetKind {
+    /// Name used in config.json, `config set budget.<key>` and alerts
+    pub fn key(&self) -> &'static str {
+        match self {
+            BudgetKind::WindowPercent => "window_percent",
+            BudgetKind::DayCost => "day_cost",
+            BudgetKind::WeekTokens => "week_tokens",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BudgetKind::WindowPercent => "5-hour window",
+            BudgetKind::DayCost => "Day cost",
+            BudgetKind::WeekTokens => "Week tokens",
+        }
+    }
+}
+
+/// Where a budget stands in its current period
+#[derive(Debug, Clone)]
+pub struct BudgetStatus {
+    pub kind: BudgetKind,
+    /// Configured budget (percent, USD or tokens)
+    pub budget: f64,
+    /// Usage so far, in the unit of the budget
+    pub value: f64,
+    /// Start of the window, day or week the usage is counted over
+    pub period_start: DateTime<Utc>,
+}
+
+impl BudgetStatus {
+    /// Share of the budget used, in percent
+    pub fn percent(&self) -> f64 {
+        match self.budget {
+            budget if budget > 0.0 => 100.0 * self.value / budget,
+            _ => 0.0,
+        }
+    }
+}
+
+/// A threshold crossed by a budget, as sent to sinks (one line of the JSONL log)
+#[derive(Debug, Clone, Serialize)]
+pub struct Alert {
+    pub timestamp: DateTime<Utc>,
+    pub budget: BudgetKind,
+    /// Percent of the budget that was crossed
+    pub threshold: u32,
+    pub value: f64,
+    pub limit: f64,
+    pub period_start: DateTime<Utc>,
+    pub message: String,
+}
+
+/// Highest threshold alerted per budget and the period it was in
+/// (~/.claude-bar/alert_state.json), so each crossing is only reported once
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertState {
+    #[serde(default)]
+    pub budgets: HashMap<BudgetKind, AlertMark>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AlertMark {
+    pub period_start: DateTime<Utc>,
+    pub threshold: u32,
+}
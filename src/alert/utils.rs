@@ -0,0 +1,211 @@
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    alert::{Alert, AlertMark, AlertState, BudgetKind, BudgetStatus},
+    analyze::{analyze_period, Analyzer},
+    cache::{write_atomic, CacheInfo, FileLock, LOCK_TIMEOUT},
+    common::{colors::*, time::{start_of_day, to_local, week_start}},
+    config::{expand_home, AlertConfig, AlertSink, ConfigInfo},
+    table::format_token_count,
+};
+
+/// Get the path to ~/.claude-bar/alert_state.json
+fn get_state_path() -> PathBuf {
+    get_data_dir().join("alert_state.json")
+}
+
+/// Get the path to the lock file guarding alert state updates
+fn get_state_lock_path() -> PathBuf {
+    get_data_dir().join("alert_state.lock")
+}
+
+/// Default file of the `log` sink, ~/.claude-bar/alerts.jsonl
+fn get_default_log_path() -> PathBuf {
+    get_data_dir().join("alerts.jsonl")
+}
+
+fn get_data_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".claude-bar")
+}
+
+/// Load the alert state, empty when missing or unreadable
+pub fn load_alert_state() -> AlertState {
+    fs::read_to_string(get_state_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Save the alert state atomically; fails silently
+pub fn save_alert_state(state: &AlertState) {
+    let path = get_state_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(content) = serde_json::to_string_pretty(state) {
+        let _ = write_atomic(&path, content.as_bytes());
+    }
+}
+
+/// Where each configured budget stands at `now`
+pub fn budget_statuses(config: &ConfigInfo, analyzer: &Analyzer, cache: &CacheInfo, now: DateTime<Utc>) -> Vec<BudgetStatus> {
+    let budgets = &config.budgets;
+    let today = to_local(now).date_naive();
+    let mut statuses = Vec::new();
+
+    if let Some(budget) = budgets.window_percent {
+        let current = analyzer.get_current();
+        let value = match analyzer.output_token_max() {
+            0 => 0.0,
+            limit => 100.0 * current.stats.weighted_output_tokens as f64 / limit as f64,
+        };
+        statuses.push(BudgetStatus { kind: BudgetKind::WindowPercent, budget, value, period_start: current.start });
+    }
+    if let Some(budget) = budgets.day_cost {
+        let start = start_of_day(today);
+        let value = analyze_period(cache, analyzer.query(), start, now).cost;
+        statuses.push(BudgetStatus { kind: BudgetKind::DayCost, budget, value, period_start: start });
+    }
+    if let Some(budget) = budgets.week_tokens {
+        let start = start_of_day(week_start(today));
+        let value = analyze_period(cache, analyzer.query(), start, now).stats.output_tokens as f64;
+        statuses.push(BudgetStatus { kind: BudgetKind::WeekTokens, budget: budget as f64, value, period_start: start });
+    }
+    statuses
+}
+
+/// Alerts for the thresholds crossed since the last check, recorded in `state`.
+/// Several thresholds crossed at once give a single alert, for the highest one;
+/// a new window, day or week starts over.
+pub fn new_alerts(statuses: &[BudgetStatus], thresholds: &[u32], state: &mut AlertState, now: DateTime<Utc>) -> Vec<Alert> {
+    statuses.iter()
+        .filter_map(|status| {
+            let crossed = thresholds.iter().copied().filter(|threshold| status.percent() >= *threshold as f64).max()?;
+            let alerted = state.budgets.get(&status.kind)
+                .filter(|mark| mark.period_start == status.period_start)
+                .map_or(0, |mark| mark.threshold);
+            if crossed <= alerted {
+                return None;
+            }
+            state.budgets.insert(status.kind, AlertMark { period_start: status.period_start, threshold: crossed });
+            Some(Alert {
+                timestamp: now,
+                budget: status.kind,
+                threshold: crossed,
+                value: status.value,
+                limit: status.budget,
+                period_start: status.period_start,
+                message: alert_message(status, crossed),
+            })
+        })
+        .collect()
+}
+
+fn alert_message(status: &BudgetStatus, threshold: u32) -> String {
+    let tokens = |value: f64| format_token_count(value.min(u32::MAX as f64) as u32, 0);
+    match status.kind {
+        BudgetKind::WindowPercent => format!(
+            "5-hour window at {:.0}% of the limit ({}% of the {:.0}% budget)",
+            status.value, threshold, status.budget
+        ),
+        BudgetKind::DayCost => format!(
+            "Today's usage at ${:.2} ({}% of the ${:.2} budget)",
+            status.value, threshold, status.budget
+        ),
+        BudgetKind::WeekTokens => format!(
+            "This week's output at {} tokens ({}% of the {} budget)",
+            tokens(status.value), threshold, tokens(status.budget)
+        ),
+    }
+}
+
+/// Check the budgets, send the alerts of new threshold crossings and remember them
+/// (nothing is sent or remembered with `dry_run`)
+///
+/// Parallel `prompt` runs check at the same time: the state is read, updated and
+/// written under a lock so a crossing is only sent by one of them. When the lock
+/// can't be taken, another process is checking and this one sends nothing.
+pub fn check_budgets(config: &ConfigInfo, analyzer: &Analyzer, cache: &CacheInfo, dry_run: bool) -> (Vec<BudgetStatus>, Vec<Alert>) {
+    let now = Utc::now();
+    let statuses = budget_statuses(config, analyzer, cache, now);
+    if statuses.is_empty() {
+        return (statuses, Vec::new());
+    }
+    if dry_run {
+        let alerts = new_alerts(&statuses, &config.alerts.thresholds, &mut load_alert_state(), now);
+        return (statuses, alerts);
+    }
+
+    let Some(_lock) = FileLock::acquire(&get_state_lock_path(), LOCK_TIMEOUT) else {
+        return (statuses, Vec::new());
+    };
+    let mut state = load_alert_state();
+    let alerts = new_alerts(&statuses, &config.alerts.thresholds, &mut state, now);
+    if !alerts.is_empty() {
+        for alert in &alerts {
+            for sink in &config.alerts.sinks {
+                send_alert(*sink, alert, &config.alerts);
+            }
+        }
+        save_alert_state(&state);
+    }
+    (statuses, alerts)
+}
+
+/// Deliver an alert to one sink. Failures are ignored and commands are not waited
+/// for: alerts must not break or slow down the status line. Spawned programs get
+/// no stdout/stderr, so they can't write into the status line or hold its pipes open.
+pub fn send_alert(sink: AlertSink, alert: &Alert, config: &AlertConfig) {
+    match sink {
+        AlertSink::Stderr => eprintln!("{yellow}⚠️  {}{reset}", alert.message, yellow = YELLOW, reset = RESET),
+        AlertSink::Notify => {
+            if let Some(program) = find_program("notify-send") {
+                let _ = Command::new(program)
+                    .args(["--app-name=Claude Bar", "Claude usage budget", &alert.message])
+                    .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+                    .spawn();
+            }
+        }
+        AlertSink::Command => {
+            if let Some(command) = &config.command {
+                let _ = Command::new("sh")
+                    .args(["-c", command])
+                    .env("CLAUDE_BAR_ALERT_BUDGET", alert.budget.key())
+                    .env("CLAUDE_BAR_ALERT_THRESHOLD", alert.threshold.to_string())
+                    .env("CLAUDE_BAR_ALERT_VALUE", format!("{:.2}", alert.value))
+                    .env("CLAUDE_BAR_ALERT_LIMIT", format!("{:.2}", alert.limit))
+                    .env("CLAUDE_BAR_ALERT_MESSAGE", &alert.message)
+                    .env("CLAUDE_BAR_ALERT_JSON", serde_json::to_string(alert).unwrap_or_default())
+                    .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+                    .spawn();
+            }
+        }
+        AlertSink::Log => {
+            let path = config.log_path.as_deref()
+                .map(|path| PathBuf::from(expand_home(path)))
+                .unwrap_or_else(get_default_log_path);
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let (Ok(line), Ok(mut file)) = (serde_json::to_string(alert), OpenOptions::new().create(true).append(true).open(path)) {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+}
+
+/// Full path of `program` when it is on the PATH
+fn find_program(program: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
@@ -1,48 +1,96 @@
 use std::collections::HashMap;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 
 use crate::{
-    analyze::{analyze_blocks, DataBlock, BlockKind}, 
-    cache::CacheInfo
+    analyze::{analyze_blocks, AnalyzerQuery, analyze_recent, analyze_weekly, build_hourly_usage, estimate_limit, model_weight, parse_drift, DataBlock, BlockKind, EstimateSource, HourlyUsage, LimitEstimate, ParseDrift, RecentUsage, WeeklyBlock}, 
+    cache::{CacheInfo, LimitKind, UsageEvent},
+    config::{ConfigInfo, LimitOverrides, Plan},
 };
 
+/// Hours of usage kept for the hourly sparkline
+const RECENT_HOURS: usize = 12;
+/// Minutes of the current block the burn rate is measured over
+const BURN_RATE_MINUTES: i64 = 30;
+
 pub struct Analyzer {
     data_blocks: HashMap<DateTime<Utc>, DataBlock>,
-    /// Mean output tokens after p20-p80 removal from limit blocks
-    output_token_max: i64,
+    /// Output tokens of the last `RECENT_HOURS` hours and 5-minute slots of the current block
+    recent: RecentUsage,
+    /// Messages of the last hours at full resolution, oldest first
+    recent_events: Vec<UsageEvent>,
+    /// 5-hour limit estimated from limit blocks, per root
+    /// (each root is a separate account with its own quota)
+    limit_estimate_by_root: HashMap<String, LimitEstimate>,
+    /// Plan whose presets stand in for limits that were never hit, and manual limits
+    plan: Plan,
+    limits: LimitOverrides,
+    /// 7-day windows ending at weekly resets, current week last
+    weekly_blocks: Vec<WeeklyBlock>,
+    /// Mean output tokens counted by each weekly cap when it was hit, per root
+    weekly_token_max_by_root: HashMap<String, HashMap<LimitKind, i64>>,
+    /// Usage lines lost since the last Claude Code upgrade, past `config.parse_warning_percent`
+    parse_drift: Option<ParseDrift>,
+    /// Filters the blocks and usage were built with
+    query: AnalyzerQuery,
 }
 impl Analyzer {
-    pub fn new(cache: &CacheInfo) -> Self {
-        // Build blocks (uses internal flattened aggregation privately)
-        let data_blocks = analyze_blocks(cache);
-        
-        // Calculate output_token_max from limit blocks using p20-p80 removal
-        let mut output_counts: Vec<i64> = data_blocks.values()
-            .filter(|b| matches!(b.kind, BlockKind::Limit))
-            .map(|b| b.stats.output_tokens)
+    /// Limits come from `config.limits` when set, otherwise from limit hits, falling
+    /// back to the presets of `config.plan`. Blocks and usage only count what `query`
+    /// selects; limits are learned from all usage of the selected roots.
+    pub fn new(cache: &CacheInfo, query: &AnalyzerQuery, config: &ConfigInfo) -> Self {
+        // The cache is flattened once per root; without usage or time filters, the
+        // usage reported is that of the selected roots together
+        let usage_by_root: HashMap<&String, HourlyUsage> = cache.roots.keys()
+            .filter(|name| query.matches_root(name))
+            .map(|name| (name, build_hourly_usage(cache, Some(name))))
             .collect();
-        
-        let output_token_max = if output_counts.is_empty() {
-            0
-        } else {
-            output_counts.sort_unstable();
-            let len = output_counts.len();
-            let p70_idx = (len as f64 * 0.7) as usize;
-            
-            // Remove  p70 outliers
-            let trimmed = &output_counts[0..p70_idx.min(len)];
-            
-            if trimmed.is_empty() {
-                output_counts[0] // fallback if too few samples
-            } else {
-                trimmed.iter().sum::<i64>() / trimmed.len() as i64
-            }
+        let usage = match query.filters_usage() || query.since.is_some() || query.until.is_some() {
+            true => build_hourly_usage(&query.apply(cache), None),
+            false => usage_by_root.values().fold(HourlyUsage::default(), |mut usage, root_usage| {
+                usage.merge(root_usage);
+                usage
+            }),
         };
+
+        let data_blocks = analyze_blocks(&usage);
+        let current_start = data_blocks.values()
+            .find(|b| b.kind == BlockKind::Current)
+            .map_or_else(Utc::now, |b| b.start);
+        let recent = analyze_recent(&usage, RECENT_HOURS, current_start);
+        let weekly_blocks = analyze_weekly(&usage);
+
+        // Limits are learned per root: a limit hit on one account says nothing about another
+        let now = Utc::now();
+        let mut limit_estimate_by_root = HashMap::new();
+        let mut weekly_token_max_by_root = HashMap::new();
+        for (name, root_usage) in &usage_by_root {
+            let hits = limit_hits(&analyze_blocks(root_usage));
+            let estimate = match config.limits.output_tokens {
+                Some(tokens) => LimitEstimate::manual(tokens, hits.len()),
+                None => estimate_limit(&hits, now, config.plan),
+            };
+            limit_estimate_by_root.insert(name.to_string(), estimate);
+
+            let root_weeks = analyze_weekly(root_usage);
+            let maxima = [LimitKind::Weekly, LimitKind::WeeklyOpus]
+                .into_iter()
+                .map(|limit| (limit, estimate_weekly_token_max(&root_weeks, limit)))
+                .collect();
+            weekly_token_max_by_root.insert(name.to_string(), maxima);
+        }
         
         Self { 
             data_blocks,
-            output_token_max,
+            recent,
+            recent_events: usage.recent_events,
+            limit_estimate_by_root,
+            plan: config.plan,
+            limits: config.limits.clone(),
+            weekly_blocks,
+            weekly_token_max_by_root,
+            parse_drift: parse_drift(cache, query, config.parse_warning_percent),
+            query: query.clone(),
         }
     }
 
@@ -66,13 +114,141 @@ impl Analyzer {
             .unwrap()
     }
 
+    /// Recent output tokens per hour and per 5 minutes of the current block
+    pub fn recent_usage(&self) -> &RecentUsage {
+        &self.recent
+    }
+
+    /// Time of the newest message (None when nothing was sent in the last hours)
+    pub fn last_message(&self) -> Option<DateTime<Utc>> {
+        self.recent_events.last().map(|event| event.timestamp)
+    }
+
+    /// Weighted output tokens per minute over the last `BURN_RATE_MINUTES` minutes
+    /// of the current block
+    pub fn burn_rate(&self) -> f64 {
+        let now = Utc::now();
+        let since = self.get_current().start.max(now - Duration::minutes(BURN_RATE_MINUTES));
+        let tokens: f64 = self.recent_events.iter()
+            .filter(|event| event.timestamp >= since)
+            .map(|event| event.output_tokens as f64 * event.model.as_deref().map_or(1.0, model_weight))
+            .sum();
+        tokens / ((now - since).num_seconds().max(60) as f64 / 60.0)
+    }
+
     /// Get all blocks (for debug purposes)
     pub fn all_blocks(&self) -> Vec<&DataBlock> {
         self.data_blocks.values().collect()
     }
     
-    /// Get calculated output token max (mean after p20-p80 removal)
+    /// Estimated 5-hour limit in weighted output tokens.
+    /// When all roots are aggregated, this is the sum of the per-root limits.
     pub fn output_token_max(&self) -> i64 {
-        self.output_token_max
+        self.limit_estimate().value
     }
-}
\ No newline at end of file
+
+    /// 5-hour limit estimate with its confidence interval and source
+    pub fn limit_estimate(&self) -> LimitEstimate {
+        let estimates: Vec<LimitEstimate> = match self.root() {
+            Some(root) => self.limit_estimate_for(root).into_iter().collect(),
+            None => self.limit_estimate_by_root.values().cloned().collect(),
+        };
+        if estimates.is_empty() {
+            match self.limits.output_tokens {
+                Some(tokens) => LimitEstimate::manual(tokens, 0),
+                None => estimate_limit(&[], Utc::now(), self.plan),
+            }
+        } else {
+            LimitEstimate::sum(&estimates)
+        }
+    }
+
+    /// 5-hour limit estimate of a single root
+    pub fn limit_estimate_for(&self, root: &str) -> Option<LimitEstimate> {
+        self.limit_estimate_by_root.get(root).cloned()
+    }
+
+    /// All weekly windows sorted by end, the current week last
+    pub fn weekly_blocks(&self) -> &[WeeklyBlock] {
+        &self.weekly_blocks
+    }
+
+    pub fn get_current_week(&self) -> &WeeklyBlock {
+        self.weekly_blocks.last().unwrap()
+    }
+
+    /// Output token limit of a weekly cap: manual, learned once the cap was hit,
+    /// else the plan preset (none for the Opus cap). Summed over aggregated roots.
+    pub fn weekly_limit(&self, limit: LimitKind) -> (i64, EstimateSource) {
+        let learned = self.weekly_token_max(limit);
+        let preset = self.plan.budget().map_or(0, |budget| budget.weekly_output_tokens);
+        match (limit, self.limits.weekly_output_tokens) {
+            (LimitKind::Weekly, Some(tokens)) => (tokens * self.accounts(), EstimateSource::Manual),
+            _ if learned > 0 => (learned, EstimateSource::Learned),
+            (LimitKind::Weekly, None) => (preset * self.accounts(), EstimateSource::Preset),
+            _ => (0, EstimateSource::Preset),
+        }
+    }
+
+    /// Messages allowed per 5-hour window: manual, else the plan preset
+    pub fn message_limit(&self) -> (i64, EstimateSource) {
+        match self.limits.messages {
+            Some(messages) => (messages * self.accounts(), EstimateSource::Manual),
+            None => {
+                let preset = self.plan.budget().map_or(0, |budget| budget.messages);
+                (preset * self.accounts(), EstimateSource::Preset)
+            }
+        }
+    }
+
+    /// Accounts (roots) the limits add up over
+    fn accounts(&self) -> i64 {
+        self.limit_estimate_by_root.len().max(1) as i64
+    }
+
+    /// Learned output token max of a weekly cap (0 until it has been hit once)
+    /// When all roots are aggregated, this is the sum of the per-root limits.
+    pub fn weekly_token_max(&self, limit: LimitKind) -> i64 {
+        self.weekly_token_max_by_root
+            .iter()
+            .filter(|(root, _)| self.root().is_none_or(|selected| selected == root.as_str()))
+            .filter_map(|(_, maxima)| maxima.get(&limit))
+            .sum()
+    }
+
+    /// Parse losses of the newest Claude Code version, when its upgrade broke parsing
+    pub fn parse_drift(&self) -> Option<&ParseDrift> {
+        self.parse_drift.as_ref()
+    }
+
+    /// Root the analysis is restricted to, if any
+    pub fn root(&self) -> Option<&str> {
+        self.query.root()
+    }
+
+    /// Filters the analysis was built with
+    pub fn query(&self) -> &AnalyzerQuery {
+        &self.query
+    }
+}
+
+/// (window end, weighted output tokens) of every limit block
+fn limit_hits(data_blocks: &HashMap<DateTime<Utc>, DataBlock>) -> Vec<(DateTime<Utc>, i64)> {
+    data_blocks.values()
+        .filter(|b| matches!(b.kind, BlockKind::Limit))
+        .map(|b| (b.end, b.stats.weighted_output_tokens))
+        .collect()
+}
+
+/// Mean output tokens counted by a weekly cap over the weeks that ended on it
+fn estimate_weekly_token_max(weekly_blocks: &[WeeklyBlock], limit: LimitKind) -> i64 {
+    let counted: Vec<i64> = weekly_blocks.iter()
+        .filter(|w| w.kind == BlockKind::Limit && w.limit == limit)
+        .map(|w| w.counted_output_tokens(limit))
+        .collect();
+
+    match counted.len() {
+        0 => 0,
+        n => counted.iter().sum::<i64>() / n as i64,
+    }
+}
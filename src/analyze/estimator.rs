@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+
+use crate::config::Plan;
+
+/// Fewer limit hits than this and the plan preset is used instead
+pub const MIN_LIMIT_SAMPLES: usize = 3;
+/// A limit hit this old counts half as much as one today (plans and limits change)
+pub const RECENCY_HALF_LIFE_DAYS: f64 = 14.0;
+/// z-score of the reported confidence interval (95%)
+const CONFIDENCE_Z: f64 = 1.96;
+
+/// Cost of an output token relative to Sonnet: limits are spent faster on Opus
+/// and slower on Haiku, so usage is compared in Sonnet-equivalent tokens
+pub fn model_weight(model: &str) -> f64 {
+    let model = model.to_lowercase();
+    if model.contains("opus") {
+        5.0
+    } else if model.contains("haiku") {
+        0.27
+    } else {
+        1.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EstimateSource {
+    /// Learned from past limit hits
+    Learned,
+    /// Preset of the configured plan (not enough limit hits yet)
+    Preset,
+    /// Set with `config set limit.*`
+    Manual,
+}
+
+impl EstimateSource {
+    /// One-letter tag shown next to limits in the status line
+    pub fn indicator(&self) -> &'static str {
+        match self {
+            EstimateSource::Learned => "L",
+            EstimateSource::Preset => "P",
+            EstimateSource::Manual => "M",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EstimateSource::Learned => "learned",
+            EstimateSource::Preset => "plan preset",
+            EstimateSource::Manual => "manual",
+        }
+    }
+}
+
+/// Estimated weighted output tokens per 5-hour window
+#[derive(Debug, Clone)]
+pub struct LimitEstimate {
+    pub value: i64,
+    /// 95% confidence interval (collapsed to `value` for a preset)
+    pub low: i64,
+    pub high: i64,
+    /// Limit hits seen, and those kept after trimming outliers
+    pub hits: usize,
+    pub samples: usize,
+    pub source: EstimateSource,
+}
+
+impl LimitEstimate {
+    fn fixed(value: i64, hits: usize, source: EstimateSource) -> Self {
+        Self { value, low: value, high: value, hits, samples: 0, source }
+    }
+
+    /// Limit set by hand; `hits` is still reported
+    pub fn manual(value: i64, hits: usize) -> Self {
+        Self::fixed(value, hits, EstimateSource::Manual)
+    }
+
+    /// Combined limit of several accounts (roots): values and bounds add up.
+    /// The source is the least reliable one involved (preset, then learned, then manual).
+    pub fn sum(estimates: &[LimitEstimate]) -> Self {
+        let source = [EstimateSource::Preset, EstimateSource::Learned]
+            .into_iter()
+            .find(|source| estimates.iter().any(|e| e.source == *source))
+            .unwrap_or(EstimateSource::Manual);
+        Self {
+            value: estimates.iter().map(|e| e.value).sum(),
+            low: estimates.iter().map(|e| e.low).sum(),
+            high: estimates.iter().map(|e| e.high).sum(),
+            hits: estimates.iter().map(|e| e.hits).sum(),
+            samples: estimates.iter().map(|e| e.samples).sum(),
+            source,
+        }
+    }
+}
+
+/// Estimate the 5-hour limit from limit hits given as (window end, weighted output tokens)
+///
+/// Hits outside p20-p80 are dropped once there are enough of them (a window hit on
+/// another device looks nearly empty here), the rest are averaged with exponential
+/// recency weights. Falls back to the plan preset below `MIN_LIMIT_SAMPLES` hits
+/// (0 for plans without one).
+pub fn estimate_limit(hits: &[(DateTime<Utc>, i64)], now: DateTime<Utc>, plan: Plan) -> LimitEstimate {
+    if hits.len() < MIN_LIMIT_SAMPLES {
+        let preset = plan.budget().map_or(0, |budget| budget.output_tokens);
+        return LimitEstimate::fixed(preset, hits.len(), EstimateSource::Preset);
+    }
+
+    let mut sorted = hits.to_vec();
+    sorted.sort_unstable_by_key(|(_, tokens)| *tokens);
+    let kept = if sorted.len() >= 5 {
+        let low = (sorted.len() as f64 * 0.2) as usize;
+        let high = (sorted.len() as f64 * 0.8).ceil() as usize;
+        &sorted[low..high]
+    } else {
+        &sorted[..]
+    };
+
+    let weighted: Vec<(f64, f64)> = kept.iter()
+        .map(|(end, tokens)| {
+            let age_days = (now - *end).num_minutes().max(0) as f64 / (24.0 * 60.0);
+            (0.5f64.powf(age_days / RECENCY_HALF_LIFE_DAYS), *tokens as f64)
+        })
+        .collect();
+
+    let weight_sum: f64 = weighted.iter().map(|(w, _)| w).sum();
+    let mean = weighted.iter().map(|(w, x)| w * x).sum::<f64>() / weight_sum;
+
+    // Effective sample size of the weighted mean, and its standard error
+    let n_eff = weight_sum.powi(2) / weighted.iter().map(|(w, _)| w * w).sum::<f64>();
+    let variance = weighted.iter().map(|(w, x)| w * (x - mean).powi(2)).sum::<f64>() / weight_sum
+        * n_eff / (n_eff - 1.0).max(1.0);
+    let margin = CONFIDENCE_Z * variance.sqrt() / n_eff.sqrt();
+
+    LimitEstimate {
+        value: mean.round() as i64,
+        low: (mean - margin).max(0.0).round() as i64,
+        high: (mean + margin).round() as i64,
+        hits: hits.len(),
+        samples: kept.len(),
+        source: EstimateSource::Learned,
+    }
+}
@@ -1,7 +1,11 @@
 mod analyzer;
+mod estimator;
 mod types;
 mod utils;
+mod query;
 
 pub use utils::*;
 pub use types::*;
-pub use analyzer::Analyzer;
\ No newline at end of file
+pub use estimator::*;
+pub use analyzer::Analyzer;
+pub use query::*;
\ No newline at end of file
@@ -0,0 +1,287 @@
+use std::{borrow::Cow, collections::HashMap, sync::LazyLock};
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use regex::Regex;
+
+use crate::{
+    cache::{CacheInfo, CachedFile, CachedFolder, CachedRoot, ModelUsage, PerHourBlock},
+    cli::QueryArgs,
+    common::time::{self, format_datetime},
+};
+
+/// Restricts reports to a period, roots, projects, models and sessions
+///
+/// Applied to the cache before blocks are built (see `apply`). Limit events are
+/// account-wide: they are only filtered by time, so windows keep their shape when
+/// a project or model is selected.
+#[derive(Debug, Clone, Default)]
+pub struct AnalyzerQuery {
+    /// Usage from this time on (hourly aggregates overlapping it count)
+    pub since: Option<DateTime<Utc>>,
+    /// Usage before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Project folder patterns: substring, or glob when they contain `*` or `?`
+    pub projects: Vec<String>,
+    /// Model name parts, case-insensitive (e.g. "opus")
+    pub models: Vec<String>,
+    /// Session id prefixes
+    pub sessions: Vec<String>,
+    /// Claude root names
+    pub roots: Vec<String>,
+}
+
+impl AnalyzerQuery {
+    /// Query of the common report flags and `--root`; times are read in the display timezone
+    pub fn from_args(args: &QueryArgs, root: Option<&str>) -> Result<Self, String> {
+        let now = Utc::now();
+        let zone = time::settings().zone;
+        let bound = |value: &Option<String>, end_of_day: bool| {
+            value.as_deref().map(|value| parse_time_bound(value, now, zone, end_of_day)).transpose()
+        };
+        Ok(Self {
+            since: bound(&args.since, false)?,
+            until: bound(&args.until, true)?,
+            projects: args.projects.clone(),
+            models: args.models.clone(),
+            sessions: args.sessions.clone(),
+            roots: root.map(str::to_string).into_iter().collect(),
+        })
+    }
+
+    /// Use `since` unless a start was given (commands with a `--days` default)
+    pub fn since_default(mut self, since: DateTime<Utc>) -> Self {
+        self.since.get_or_insert(since);
+        self
+    }
+
+    /// The single root selected, if any
+    pub fn root(&self) -> Option<&str> {
+        match self.roots.as_slice() {
+            [root] => Some(root),
+            _ => None,
+        }
+    }
+
+    /// Days between `since` and `until` (or now)
+    pub fn days(&self) -> Option<i64> {
+        let since = self.since?;
+        Some((self.until.unwrap_or_else(Utc::now) - since).num_days().max(1))
+    }
+
+    pub fn matches_root(&self, root: &str) -> bool {
+        self.roots.is_empty() || self.roots.iter().any(|r| r == root)
+    }
+
+    pub fn matches_project(&self, folder: &str) -> bool {
+        self.projects.is_empty() || self.projects.iter().any(|pattern| match pattern.contains(['*', '?']) {
+            true => glob_match(pattern, folder),
+            false => folder.contains(pattern.as_str()),
+        })
+    }
+
+    pub fn matches_session(&self, file: &CachedFile) -> bool {
+        self.sessions.is_empty() || self.sessions.iter().any(|prefix| file.session_key().starts_with(prefix.as_str()))
+    }
+
+    pub fn matches_model(&self, model: &str) -> bool {
+        let model = model.to_lowercase();
+        self.models.is_empty() || self.models.iter().any(|part| model.contains(&part.to_lowercase()))
+    }
+
+    pub fn matches_time(&self, timestamp: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|since| timestamp >= since) && self.until.is_none_or(|until| timestamp < until)
+    }
+
+    /// Whether the hour starting at `hour_start` overlaps the period
+    pub fn matches_hour(&self, hour_start: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|since| hour_start + Duration::hours(1) > since)
+            && self.until.is_none_or(|until| hour_start < until)
+    }
+
+    /// Usage is restricted to some projects, sessions or models
+    pub fn filters_usage(&self) -> bool {
+        !self.projects.is_empty() || !self.sessions.is_empty() || !self.models.is_empty()
+    }
+
+    /// Nothing restricted: reports read the cache as is
+    pub fn is_empty(&self) -> bool {
+        self.since.is_none() && self.until.is_none() && self.roots.is_empty() && !self.filters_usage()
+    }
+
+    /// The part of the cache the query selects: matching roots, and in them the usage
+    /// of matching projects, sessions, models and hours, with the limit events of the period
+    pub fn apply<'a>(&self, cache: &'a CacheInfo) -> Cow<'a, CacheInfo> {
+        if self.is_empty() {
+            return Cow::Borrowed(cache);
+        }
+
+        let mut filtered = CacheInfo::default();
+        for (root_name, root) in cache.roots.iter().filter(|(name, _)| self.matches_root(name)) {
+            let mut filtered_root = CachedRoot::default();
+            for (folder_name, folder) in &root.folders {
+                let project_matches = self.matches_project(folder_name);
+                let files: HashMap<String, CachedFile> = folder.files.iter()
+                    .map(|(file_name, file)| {
+                        let usage = project_matches && self.matches_session(file);
+                        (file_name.clone(), self.filter_file(file, usage))
+                    })
+                    .collect();
+                filtered_root.folders.insert(folder_name.clone(), CachedFolder { files });
+            }
+            filtered.roots.insert(root_name.clone(), filtered_root);
+        }
+        Cow::Owned(filtered)
+    }
+
+    /// Copy of a file with the limit events of the period, and its usage when `usage` is set
+    fn filter_file(&self, file: &CachedFile, usage: bool) -> CachedFile {
+        CachedFile {
+            file_name: file.file_name.clone(),
+            cache_time: file.cache_time,
+            blocks: file.blocks.iter()
+                .filter(|(ts, _)| self.matches_time(**ts))
+                .map(|(ts, block)| (*ts, block.clone()))
+                .collect(),
+            per_hour: match usage {
+                true => file.per_hour.iter()
+                    .filter(|(hour_start, _)| self.matches_hour(**hour_start))
+                    .map(|(hour_start, hour)| (*hour_start, self.filter_models(hour)))
+                    .collect(),
+                false => HashMap::new(),
+            },
+            session_id: file.session_id.clone(),
+            missing_since: file.missing_since,
+            archived_until: file.archived_until,
+            claude_version: file.claude_version.clone(),
+            parse_errors: Vec::new(),
+            recent_events: match usage {
+                true => file.recent_events.iter()
+                    .filter(|event| self.matches_time(event.timestamp))
+                    .filter(|event| event.model.as_deref().is_none_or(|model| self.matches_model(model)))
+                    .cloned()
+                    .collect(),
+                false => Vec::new(),
+            },
+            open_tool_calls: HashMap::new(),
+            cache_status: file.cache_status.clone(),
+            modified_time: file.modified_time,
+            created_time: file.created_time,
+            size_bytes: file.size_bytes,
+        }
+    }
+
+    /// Hour restricted to the selected models: token totals and assistant messages are
+    /// recomputed from the per-model split, 5-minute slots are scaled down; user
+    /// messages, tools and subagent usage are not split by model and stay as they are
+    fn filter_models(&self, hour: &PerHourBlock) -> PerHourBlock {
+        let mut hour = hour.clone();
+        if self.models.is_empty() {
+            return hour;
+        }
+
+        hour.models.retain(|model, _| self.matches_model(model));
+        let mut total = ModelUsage::default();
+        for usage in hour.models.values() {
+            total.merge(usage);
+        }
+        let kept = match hour.output_tokens {
+            0 => 0.0,
+            output => total.output_tokens as f64 / output as f64,
+        };
+        for slot in hour.output_by_5min.iter_mut() {
+            *slot = (*slot as f64 * kept).round() as u32;
+        }
+        hour.input_tokens = total.input_tokens;
+        hour.output_tokens = total.output_tokens;
+        hour.cache_creation_tokens = total.cache_creation_tokens;
+        hour.cache_read_tokens = total.cache_read_tokens;
+        hour.assistant_messages = total.messages;
+        hour
+    }
+
+    /// Filters for report headers, e.g. "since 10-11 00:00, project: my-app"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(since) = self.since {
+            parts.push(format!("since {}", format_datetime(since)));
+        }
+        if let Some(until) = self.until {
+            parts.push(format!("until {}", format_datetime(until)));
+        }
+        for (label, values) in [("project", &self.projects), ("model", &self.models), ("session", &self.sessions), ("root", &self.roots)] {
+            if !values.is_empty() {
+                parts.push(format!("{}: {}", label, values.join("|")));
+            }
+        }
+        parts.join(", ")
+    }
+}
+
+/// `7d`, `12h`, `2w`
+static RELATIVE_BOUND: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\d+)\s*([hdw])$").unwrap());
+
+/// Parse `--since`/`--until` values:
+/// - `7d`, `12h`, `2w`: that long before `now`
+/// - `2026-10-01`: midnight in `zone`, or the next midnight when `end_of_day` (whole day included)
+/// - `2026-10-01T14:30` in `zone`, or an RFC 3339 time
+pub fn parse_time_bound(value: &str, now: DateTime<Utc>, zone: Tz, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    if let Some(caps) = RELATIVE_BOUND.captures(value) {
+        let count: i64 = caps[1].parse().map_err(|_| format!("Invalid time '{}'", value))?;
+        let span = match &caps[2] {
+            "h" => Duration::hours(count),
+            "d" => Duration::days(count),
+            _ => Duration::weeks(count),
+        };
+        return Ok(now - span);
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let date = if end_of_day { date.succ_opt().unwrap_or(date) } else { date };
+        return local_to_utc(date.and_time(chrono::NaiveTime::MIN), zone, value);
+    }
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M") {
+        return local_to_utc(datetime, zone, value);
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .map_err(|_| format!("Invalid time '{}' (expected 7d, 12h, 2w, 2026-10-01, 2026-10-01T14:30 or RFC 3339)", value))
+}
+
+fn local_to_utc(local: NaiveDateTime, zone: Tz, value: &str) -> Result<DateTime<Utc>, String> {
+    zone.from_local_datetime(&local)
+        .earliest()
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .ok_or_else(|| format!("Time '{}' does not exist in {}", value, zone.name()))
+}
+
+/// `*` matches any run of characters, `?` a single one; the whole text must match
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    // Last `*` seen and the text position it was tried at, to backtrack
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
@@ -1,5 +1,56 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 
+use crate::cache::{LimitKind, ModelUsage, PerHourBlock, UsageEvent};
+
+/// Web search price, per request (USD 10 per 1,000 searches)
+pub const WEB_SEARCH_COST_PER_REQUEST: f64 = 0.01;
+
+/// API list price of a model, in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    /// 5-minute cache writes
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl ModelPrice {
+    const fn new(input: f64, output: f64) -> Self {
+        Self { input, output, cache_write: input * 1.25, cache_read: input * 0.1 }
+    }
+
+    /// Cost of `usage` in USD
+    pub fn cost(&self, usage: &ModelUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_tokens as f64 * self.cache_write
+            + usage.cache_read_tokens as f64 * self.cache_read) / 1_000_000.0
+    }
+}
+
+/// Prices by model name part, first match wins (older Opus and Haiku models cost differently)
+const MODEL_PRICES: [(&str, ModelPrice); 8] = [
+    ("opus-4-1", ModelPrice::new(15.0, 75.0)),
+    ("opus-4-2025", ModelPrice::new(15.0, 75.0)),
+    ("3-opus", ModelPrice::new(15.0, 75.0)),
+    ("opus", ModelPrice::new(5.0, 25.0)),
+    ("3-5-haiku", ModelPrice::new(0.8, 4.0)),
+    ("3-haiku", ModelPrice::new(0.25, 1.25)),
+    ("haiku", ModelPrice::new(1.0, 5.0)),
+    ("sonnet", ModelPrice::new(3.0, 15.0)),
+];
+
+/// List price of a model; unknown models are priced as Sonnet
+pub fn model_price(model: &str) -> ModelPrice {
+    let model = model.to_lowercase();
+    MODEL_PRICES.iter()
+        .find(|(part, _)| model.contains(part))
+        .map_or(ModelPrice::new(3.0, 15.0), |(_, price)| *price)
+}
+
 #[derive(Debug, Clone)]
 pub struct LimitBlock {
     /// Timestamp when the block was lifted/reset (if available)
@@ -21,10 +72,44 @@ pub struct DataStats {
     pub cache_creation_tokens: i64,
     pub cache_read_tokens: i64,
     pub total_tokens: i64,
+    /// Output tokens weighted by model cost (Sonnet-equivalent), what limits are compared in
+    pub weighted_output_tokens: i64,
     pub assistant_messages: i64,
     pub user_messages: i64,
     pub total_content_length: i64,
     pub entry_count: i64,
+    /// Part of total_tokens spent by subagents (sidechains)
+    pub sidechain_tokens: i64,
+    /// Part of output_tokens spent by subagents (sidechains)
+    pub sidechain_output_tokens: i64,
+    /// Transcript lines with usage that were parsed / could not be parsed
+    pub usage_lines: i64,
+    pub lost_usage_lines: i64,
+    /// Web searches run by the API (billed per request)
+    pub web_search_requests: i64,
+}
+
+impl DataStats {
+    /// Share of output tokens spent by subagents, in percent
+    pub fn sidechain_percent(&self) -> f64 {
+        match self.output_tokens {
+            0 => 0.0,
+            total => 100.0 * self.sidechain_output_tokens as f64 / total as f64,
+        }
+    }
+
+    /// Estimated web search cost in USD
+    pub fn web_search_cost(&self) -> f64 {
+        self.web_search_requests as f64 * WEB_SEARCH_COST_PER_REQUEST
+    }
+
+    /// Share of the lines with usage that could not be parsed, in percent
+    pub fn lost_usage_percent(&self) -> f64 {
+        match self.usage_lines + self.lost_usage_lines {
+            0 => 0.0,
+            total => 100.0 * self.lost_usage_lines as f64 / total as f64,
+        }
+    }
 }
 
 /// Unified block representation with type + time span + stats
@@ -38,3 +123,103 @@ pub struct DataBlock {
     pub unlock_timestamp: Option<DateTime<Utc>>, // only for Limit/Current when applicable
     pub stats: DataStats,
 }
+
+/// 7-day window: the week before a weekly reset, or the current week
+#[derive(Debug, Clone)]
+pub struct WeeklyBlock {
+    /// Limit for past weekly resets, Current for the week in progress
+    pub kind: BlockKind,
+    /// Weekly cap that was hit (Weekly for a current week without a known reset)
+    pub limit: LimitKind,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub unlock_timestamp: Option<DateTime<Utc>>,
+    pub stats: DataStats,
+    /// Part of output_tokens generated by Opus models (what the Opus weekly cap counts)
+    pub opus_output_tokens: i64,
+}
+
+impl WeeklyBlock {
+    /// Output tokens counted against the given weekly cap
+    pub fn counted_output_tokens(&self, limit: LimitKind) -> i64 {
+        match limit {
+            LimitKind::WeeklyOpus => self.opus_output_tokens,
+            _ => self.stats.output_tokens,
+        }
+    }
+}
+
+/// Usage of a cache flattened by hour, from which blocks, weekly windows and recent
+/// usage are derived (see `build_hourly_usage`)
+#[derive(Debug, Clone, Default)]
+pub struct HourlyUsage {
+    /// 5-hour windows ended by a limit, keyed by start
+    pub limit_blocks: HashMap<DateTime<Utc>, LimitBlock>,
+    /// Weekly cap resets, rounded to the hour and sorted
+    pub weekly_resets: Vec<(LimitKind, DateTime<Utc>)>,
+    /// Usage of all files, keyed by hour start
+    pub per_hour: HashMap<DateTime<Utc>, PerHourBlock>,
+    /// Messages of the last `RECENT_EVENT_HOURS` hours, oldest first
+    pub recent_events: Vec<UsageEvent>,
+}
+
+impl HourlyUsage {
+    /// Add the usage of `other`, e.g. of another root
+    pub fn merge(&mut self, other: &HourlyUsage) {
+        for (start, block) in &other.limit_blocks {
+            self.limit_blocks.entry(*start).or_insert_with(|| block.clone());
+        }
+        self.weekly_resets.extend(&other.weekly_resets);
+        self.weekly_resets.sort_by_key(|(kind, unlock)| (*unlock, *kind as u8));
+        self.weekly_resets.dedup();
+        for (hour_start, ph) in &other.per_hour {
+            match self.per_hour.get_mut(hour_start) {
+                Some(block) => block.merge(ph),
+                None => {
+                    self.per_hour.insert(*hour_start, ph.clone());
+                }
+            }
+        }
+        self.recent_events.extend(other.recent_events.iter().cloned());
+        self.recent_events.sort_by_key(|event| event.timestamp);
+    }
+}
+
+/// Usage lines lost by the parser since the last Claude Code upgrade, when that
+/// crossed the warning threshold (see `parse_drift`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseDrift {
+    /// Newest Claude Code version of the transcripts
+    pub version: String,
+    /// Version before it, if any
+    pub previous_version: Option<String>,
+    pub lost_usage_percent: f64,
+    pub previous_lost_usage_percent: f64,
+}
+
+/// Output tokens over recent time buckets, oldest first (for sparklines)
+#[derive(Debug, Clone, Default)]
+pub struct RecentUsage {
+    /// One bucket per hour, the current hour last
+    pub hourly: Vec<i64>,
+    /// One bucket per 5 minutes from the start of the current block up to now
+    pub five_minutes: Vec<i64>,
+}
+
+/// Usage of one period, for comparisons (see `analyze_period`)
+#[derive(Debug, Clone, Default)]
+pub struct PeriodStats {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub stats: DataStats,
+    /// Estimated API cost in USD: tokens at list prices plus web searches
+    pub cost: f64,
+    /// 5-hour limits hit during the period
+    pub limit_hits: usize,
+    /// Sessions with usage in the period
+    pub sessions: usize,
+    /// Output tokens per project folder
+    pub projects: HashMap<String, i64>,
+    /// Output tokens per model
+    pub models: HashMap<String, i64>,
+}
@@ -1,93 +1,285 @@
 use chrono::{DateTime, Utc, Duration};
-use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, HashSet};
 
 use crate::{
-    analyze::{BlockKind, DataBlock, DataStats, LimitBlock},
-    cache::{CacheInfo, PerHourBlock}, common::duration::round_to_hour_boundary
+    analyze::{model_price, model_weight, AnalyzerQuery, BlockKind, DataBlock, DataStats, HourlyUsage, LimitBlock, ParseDrift, PeriodStats, RecentUsage, WeeklyBlock},
+    cache::{CacheInfo, LimitKind, ParseHealth, PerHourBlock, UsageEvent, RECENT_EVENT_HOURS}, common::duration::{round_to_hour_boundary, round_up_to_hour_boundary}
 };
 
 // STEP 1: Find FIXED 5-hour windows from limit messages
-/// Flatten all per-hour usage across all files into a single map keyed by hour start.
-fn build_per_hour_agg(cache: &CacheInfo) -> (HashMap<DateTime<Utc>, LimitBlock>, HashMap<DateTime<Utc>, PerHourBlock>) {
-    let mut limit_blocks: HashMap<DateTime<Utc>, LimitBlock> = HashMap::new();
-    let mut per_hour_block: HashMap<DateTime<Utc>, PerHourBlock> = HashMap::new();
+/// Flatten all per-hour usage across all files into a single map keyed by hour start,
+/// with the limit windows, weekly resets and recent messages of the same files.
+/// Only roots matching `root` are included (all roots when `None`).
+pub fn build_hourly_usage(cache: &CacheInfo, root: Option<&str>) -> HourlyUsage {
+    let mut usage = HourlyUsage::default();
+    // Several messages usually report the same reset
+    let mut weekly_resets: HashSet<(LimitKind, DateTime<Utc>)> = HashSet::new();
+    let horizon = Utc::now() - Duration::hours(RECENT_EVENT_HOURS);
 
-    for (_folder, folder) in &cache.folders {
+    let folders = cache.selected_roots(root).flat_map(|(_root, cached_root)| &cached_root.folders);
+    for (_folder, folder) in folders {
         for (_file, file) in &folder.files {
             // Aggregate per-hour blocks
             for (hour_start, ph) in &file.per_hour {
-                match per_hour_block.entry(*hour_start) {
+                match usage.per_hour.entry(*hour_start) {
                     Entry::Vacant(entry) => {
                         entry.insert(ph.clone());
                     },
-                    Entry::Occupied(mut entry) => {
-                        let block = entry.get_mut();
-                        if ph.min_timestamp < block.min_timestamp {
-                            block.min_timestamp = ph.min_timestamp;
-                        }
-                        if ph.max_timestamp > block.max_timestamp {
-                            block.max_timestamp = ph.max_timestamp;
-                        }
-                        block.input_tokens += ph.input_tokens;
-                        block.output_tokens += ph.output_tokens;
-                        block.cache_creation_tokens += ph.cache_creation_tokens;
-                        block.cache_read_tokens += ph.cache_read_tokens;
-                        block.assistant_messages += ph.assistant_messages;
-                        block.user_messages += ph.user_messages;
-                        block.total_content_length += ph.total_content_length;
-                        block.entry_count += ph.entry_count;
-                    }
+                    Entry::Occupied(mut entry) => entry.get_mut().merge(ph),
                 }
             }
             
-            // Collect limit blocks
-            for (_ts, block) in &file.blocks {
-                if let Some(unlock) = block.unlock_timestamp {
-                    let start = unlock - Duration::hours(5);
-                    limit_blocks.entry(start).or_insert(LimitBlock {
-                        unlock_timestamp: unlock,
-                    });
+            // Collect limit blocks (weekly caps get their own windows, see analyze_weekly)
+            for (ts, block) in &file.blocks {
+                let Some(unlock) = block.unlock_timestamp(*ts) else {
+                    continue;
+                };
+                match block.kind {
+                    LimitKind::FiveHour => {
+                        usage.limit_blocks.entry(unlock - Duration::hours(5)).or_insert(LimitBlock {
+                            unlock_timestamp: unlock,
+                        });
+                    }
+                    kind => {
+                        weekly_resets.insert((kind, round_to_hour_boundary(unlock)));
+                    }
                 }
             }
+
+            usage.recent_events.extend(file.recent_events.iter().filter(|event| event.timestamp >= horizon).cloned());
         }
     }
 
-    (limit_blocks, per_hour_block)
+    usage.weekly_resets = weekly_resets.into_iter().collect();
+    usage.weekly_resets.sort_by_key(|(kind, unlock)| (*unlock, *kind as u8));
+    usage.recent_events.sort_by_key(|event| event.timestamp);
+    usage
 }
 
 /// Build limit windows from cache limits and populate aggregates from pre-aggregated per-hour data.
-pub fn analyze_blocks(cache: &CacheInfo) -> HashMap<DateTime<Utc>, DataBlock> {
-      let (limit_blocks, per_hour) = build_per_hour_agg(cache);
+pub fn analyze_blocks(usage: &HourlyUsage) -> HashMap<DateTime<Utc>, DataBlock> {
+      let HourlyUsage { limit_blocks, per_hour, recent_events, .. } = usage;
       let mut result: HashMap<DateTime<Utc>, DataBlock> = HashMap::new();
       
       // 1) Create limit blocks and mark their hours as "occupied"
       let mut occupied_hours = HashSet::new();
       
-      for (start, lb) in &limit_blocks {
+      for (start, lb) in limit_blocks {
           let end = lb.unlock_timestamp;
-          let block = create_limit_block(*start, end, &per_hour, &mut occupied_hours);
+          let block = create_limit_block(*start, end, per_hour, &mut occupied_hours);
           result.insert(*start, block);
       }
 
-      // 2) Create individual gap blocks for free hours
+      // 2) The window in progress, started by the first message after the last one
+      //    expired (minute resolution from the recent events, hours from then on)
+      let now = Utc::now();
+      let limited = limit_blocks.iter().any(|(start, lb)| *start <= now && now < lb.unlock_timestamp);
+      let last_unlock = limit_blocks.values()
+          .map(|lb| lb.unlock_timestamp)
+          .filter(|unlock| *unlock <= now)
+          .max();
+      if let Some(start) = active_window_start(recent_events, last_unlock, now).filter(|_| !limited) {
+          let block = create_window_block(start, now, per_hour, &mut occupied_hours);
+          result.insert(start, block);
+      }
+
+      // 3) Create individual gap blocks for free hours
       let hour_keys: Vec<DateTime<Utc>> = per_hour.keys().cloned().collect();
 
       for &hour in &hour_keys {
           if !occupied_hours.contains(&hour) {
               // This is a free hour - create a 1-hour gap block
-              let gap_block = create_gap_block(&hour, &per_hour);
+              let gap_block = create_gap_block(&hour, per_hour);
               result.insert(hour, gap_block);
           }
       }
 
       group_consecutive_gaps(&mut result);
       
-      // 3) Add current block to ensure one always exists
+      // 4) Add current block to ensure one always exists
       add_current_block(&mut result);
 
       result
 }
 
+/// Start of the 5-hour window containing `now`, if any: windows are chained from
+/// the first event after `last_unlock`, each one starting at the first message
+/// after the previous one expired. Only the events of the last `RECENT_EVENT_HOURS`
+/// hours are known, so a window started before them is seen as starting later.
+pub fn active_window_start(events: &[UsageEvent], last_unlock: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let window = Duration::hours(5);
+    let mut start: Option<DateTime<Utc>> = None;
+    for event in events.iter().filter(|event| last_unlock.is_none_or(|unlock| event.timestamp >= unlock)) {
+        if event.timestamp > now {
+            break;
+        }
+        if start.is_none_or(|start| event.timestamp >= start + window) {
+            start = Some(event.timestamp);
+        }
+    }
+    start.filter(|start| now < *start + window)
+}
+
+/// Build 7-day windows ending at each weekly reset, plus the current week (last element).
+/// The current week ends at a pending weekly reset when there is one, otherwise it is
+/// the rolling 7 days up to the current hour.
+pub fn analyze_weekly(usage: &HourlyUsage) -> Vec<WeeklyBlock> {
+    let HourlyUsage { weekly_resets: resets, per_hour, .. } = usage;
+    let now = Utc::now();
+
+    let mut result: Vec<WeeklyBlock> = resets.iter()
+        .filter(|(_, unlock)| *unlock <= now)
+        .map(|(limit, unlock)| create_weekly_block(BlockKind::Limit, *limit, *unlock, Some(*unlock), per_hour))
+        .collect();
+
+    // A pending reset bounds the current week; the overall cap wins over the Opus one
+    let pending = resets.iter()
+        .filter(|(_, unlock)| *unlock > now)
+        .min_by_key(|(kind, unlock)| (*kind as u8, *unlock));
+    let current = match pending {
+        Some((limit, unlock)) => create_weekly_block(BlockKind::Current, *limit, *unlock, Some(*unlock), per_hour),
+        None => {
+            let end = round_to_hour_boundary(now) + Duration::hours(1);
+            create_weekly_block(BlockKind::Current, LimitKind::Weekly, end, None, per_hour)
+        }
+    };
+    result.push(current);
+    result
+}
+
+/// Output tokens of the last `hours` hours and of the 5-minute slots from
+/// `window_start` up to now, oldest first (see `RecentUsage`)
+pub fn analyze_recent(usage: &HourlyUsage, hours: usize, window_start: DateTime<Utc>) -> RecentUsage {
+    let per_hour = &usage.per_hour;
+    let now = Utc::now();
+    let current_hour = round_to_hour_boundary(now);
+
+    let hourly = (0..hours as i64).rev()
+        .map(|ago| current_hour - Duration::hours(ago))
+        .map(|hour| per_hour.get(&hour).map_or(0, |ph| ph.output_tokens as i64))
+        .collect();
+
+    let slot = Duration::minutes(5);
+    let first_slot = round_to_hour_boundary(window_start)
+        + slot * ((window_start - round_to_hour_boundary(window_start)).num_minutes() / 5) as i32;
+    let slots = ((now - first_slot).num_minutes() / 5 + 1).max(0) as usize;
+    let mut five_minutes = vec![0; slots];
+    for ph in per_hour.values().filter(|ph| ph.hour_end >= first_slot && ph.hour_start <= now) {
+        for (index, tokens) in ph.output_by_5min.iter().enumerate() {
+            let start = ph.hour_start + slot * index as i32;
+            if start >= first_slot && start <= now {
+                five_minutes[((start - first_slot).num_minutes() / 5) as usize] += *tokens as i64;
+            }
+        }
+    }
+
+    RecentUsage { hourly, five_minutes }
+}
+
+/// Parse health of the newest Claude Code version in the roots `query` selects, against
+/// the version before it: Some when the newest one loses at least `threshold` percent of
+/// its usage lines and the previous one did not, i.e. the upgrade changed the transcripts
+pub fn parse_drift(cache: &CacheInfo, query: &AnalyzerQuery, threshold: f64) -> Option<ParseDrift> {
+    let mut by_version: BTreeMap<Vec<u64>, (&str, ParseHealth)> = BTreeMap::new();
+    let files = cache.roots.iter()
+        .filter(|(name, _)| query.matches_root(name))
+        .flat_map(|(_, cached_root)| cached_root.folders.values())
+        .flat_map(|folder| folder.files.values());
+    for file in files {
+        let Some(version) = file.claude_version.as_deref() else {
+            continue;
+        };
+        let (_, health) = by_version.entry(version_key(version)).or_insert((version, ParseHealth::default()));
+        for hour in file.per_hour.values() {
+            health.merge(&hour.parse_health);
+        }
+    }
+
+    let mut versions = by_version.into_values().rev();
+    let (version, health) = versions.next()?;
+    let previous = versions.next();
+    let lost_usage_percent = health.lost_usage_percent();
+    let previous_lost_usage_percent = previous.as_ref().map_or(0.0, |(_, health)| health.lost_usage_percent());
+    (lost_usage_percent >= threshold && previous_lost_usage_percent < threshold).then(|| ParseDrift {
+        version: version.to_string(),
+        previous_version: previous.map(|(version, _)| version.to_string()),
+        lost_usage_percent,
+        previous_lost_usage_percent,
+    })
+}
+
+/// Numeric parts of a version such as "2.0.14", so that 2.0.10 sorts after 2.0.9
+fn version_key(version: &str) -> Vec<u64> {
+    version.split(['.', '-']).map(|part| part.parse().unwrap_or(0)).collect()
+}
+
+/// Totals of `[start, end)` for the usage `query` selects, from the same per-hour
+/// aggregates as the blocks. An hour counts in the period it starts in, so adjacent
+/// periods that split an hour (e.g. at 14:30) never both count it. With project, model
+/// or session filters, only the limits hit in windows they had usage in are counted.
+pub fn analyze_period(cache: &CacheInfo, query: &AnalyzerQuery, start: DateTime<Utc>, end: DateTime<Utc>) -> PeriodStats {
+    // Overlap with [ceil(start), ceil(end)) is the same as starting in [start, end)
+    let query = AnalyzerQuery {
+        since: Some(round_up_to_hour_boundary(start)),
+        until: Some(round_up_to_hour_boundary(end)),
+        ..query.clone()
+    };
+    let filtered = query.apply(cache);
+    let HourlyUsage { limit_blocks, per_hour, .. } = build_hourly_usage(&filtered, None);
+
+    let mut period = PeriodStats { start, end, ..PeriodStats::default() };
+    for ph in per_hour.values() {
+        merge_per_hour_into_stats(&mut period.stats, ph);
+        for (model, usage) in &ph.models {
+            period.cost += model_price(model).cost(usage);
+            *period.models.entry(model.clone()).or_default() += usage.output_tokens as i64;
+        }
+    }
+    period.cost += period.stats.web_search_cost();
+    period.limit_hits = limit_blocks.iter()
+        .filter(|(window_start, lb)| {
+            !query.filters_usage()
+                || per_hour.keys().any(|hour| *hour + Duration::hours(1) > **window_start && *hour < lb.unlock_timestamp)
+        })
+        .count();
+
+    let mut sessions = HashSet::new();
+    for cached_root in filtered.roots.values() {
+        for (folder_name, folder) in &cached_root.folders {
+            for file in folder.files.values().filter(|file| !file.per_hour.is_empty()) {
+                sessions.insert(file.session_key());
+                *period.projects.entry(folder_name.clone()).or_default() += file.per_hour.values()
+                    .map(|ph| ph.output_tokens as i64)
+                    .sum::<i64>();
+            }
+        }
+    }
+    period.sessions = sessions.len();
+    period
+}
+
+fn create_weekly_block(
+    kind: BlockKind,
+    limit: LimitKind,
+    end: DateTime<Utc>,
+    unlock_timestamp: Option<DateTime<Utc>>,
+    per_hour: &HashMap<DateTime<Utc>, PerHourBlock>,
+) -> WeeklyBlock {
+    let start = end - Duration::days(7);
+    let mut stats = DataStats::default();
+    let mut opus_output_tokens = 0;
+
+    for ph in per_hour.values().filter(|ph| ph.hour_start >= start && ph.hour_start < end) {
+        merge_per_hour_into_stats(&mut stats, ph);
+        opus_output_tokens += ph.models.iter()
+            .filter(|(model, _)| model.to_lowercase().contains("opus"))
+            .map(|(_, usage)| usage.output_tokens as i64)
+            .sum::<i64>();
+    }
+
+    WeeklyBlock { kind, limit, start, end, unlock_timestamp, stats, opus_output_tokens }
+}
+
 fn group_consecutive_gaps(result: &mut HashMap<DateTime<Utc>, DataBlock>) {
     let mut keys: Vec<DateTime<Utc>> = result.iter()
         .filter_map(|(k, v)| match v.kind {
@@ -167,6 +359,20 @@ fn calculate_total_tokens(ph: &PerHourBlock) -> i64 {
     + ph.cache_read_tokens as i64
 }
 
+fn calculate_sidechain_tokens(ph: &PerHourBlock) -> i64 {
+    ph.sidechain.input_tokens as i64
+    + ph.sidechain.output_tokens as i64
+    + ph.sidechain.cache_creation_tokens as i64
+    + ph.sidechain.cache_read_tokens as i64
+}
+
+fn calculate_weighted_output_tokens(ph: &PerHourBlock) -> i64 {
+    ph.models.iter()
+        .map(|(model, usage)| usage.output_tokens as f64 * model_weight(model))
+        .sum::<f64>()
+        .round() as i64
+}
+
 fn create_stats_from_per_hour(ph: &PerHourBlock) -> DataStats {
     DataStats {
         input_tokens: ph.input_tokens as i64,
@@ -174,10 +380,16 @@ fn create_stats_from_per_hour(ph: &PerHourBlock) -> DataStats {
         cache_creation_tokens: ph.cache_creation_tokens as i64,
         cache_read_tokens: ph.cache_read_tokens as i64,
         total_tokens: calculate_total_tokens(ph),
+        weighted_output_tokens: calculate_weighted_output_tokens(ph),
         assistant_messages: ph.assistant_messages as i64,
         user_messages: ph.user_messages as i64,
         total_content_length: ph.total_content_length as i64,
         entry_count: ph.entry_count as i64,
+        sidechain_tokens: calculate_sidechain_tokens(ph),
+        sidechain_output_tokens: ph.sidechain.output_tokens as i64,
+        usage_lines: ph.parse_health.usage_lines as i64,
+        lost_usage_lines: ph.parse_health.lost_usage_lines as i64,
+        web_search_requests: ph.web_search_requests as i64,
     }
 }
 
@@ -187,10 +399,16 @@ fn merge_stats(stats: &mut DataStats, to_add: &DataStats) {
     stats.cache_creation_tokens += to_add.cache_creation_tokens;
     stats.cache_read_tokens += to_add.cache_read_tokens;
     stats.total_tokens += to_add.total_tokens;
+    stats.weighted_output_tokens += to_add.weighted_output_tokens;
     stats.assistant_messages += to_add.assistant_messages;
     stats.user_messages += to_add.user_messages;
     stats.total_content_length += to_add.total_content_length;
     stats.entry_count += to_add.entry_count;
+    stats.sidechain_tokens += to_add.sidechain_tokens;
+    stats.sidechain_output_tokens += to_add.sidechain_output_tokens;
+    stats.usage_lines += to_add.usage_lines;
+    stats.lost_usage_lines += to_add.lost_usage_lines;
+    stats.web_search_requests += to_add.web_search_requests;
 }
 
 fn merge_per_hour_into_stats(stats: &mut DataStats, ph: &PerHourBlock) {
@@ -199,10 +417,16 @@ fn merge_per_hour_into_stats(stats: &mut DataStats, ph: &PerHourBlock) {
     stats.cache_creation_tokens += ph.cache_creation_tokens as i64;
     stats.cache_read_tokens += ph.cache_read_tokens as i64;
     stats.total_tokens += calculate_total_tokens(ph);
+    stats.weighted_output_tokens += calculate_weighted_output_tokens(ph);
     stats.assistant_messages += ph.assistant_messages as i64;
     stats.user_messages += ph.user_messages as i64;
     stats.total_content_length += ph.total_content_length as i64;
     stats.entry_count += ph.entry_count as i64;
+    stats.sidechain_tokens += calculate_sidechain_tokens(ph);
+    stats.sidechain_output_tokens += ph.sidechain.output_tokens as i64;
+    stats.usage_lines += ph.parse_health.usage_lines as i64;
+    stats.lost_usage_lines += ph.parse_health.lost_usage_lines as i64;
+    stats.web_search_requests += ph.web_search_requests as i64;
 }
 
 // Create a limit block from per-hour data
@@ -240,6 +464,39 @@ fn create_limit_block(
     }
 }
 
+/// Create the window in progress from `start` (exact) to now, from the hours not
+/// already taken by a limit block (the hour it starts in is counted whole)
+fn create_window_block(
+    start: DateTime<Utc>,
+    now: DateTime<Utc>,
+    per_hour: &HashMap<DateTime<Utc>, PerHourBlock>,
+    occupied_hours: &mut HashSet<DateTime<Utc>>,
+) -> DataBlock {
+    let mut stats = DataStats::default();
+    let mut max_timestamp = start;
+
+    let mut current = round_to_hour_boundary(start);
+    while current <= now {
+        if occupied_hours.insert(current) {
+            if let Some(ph) = per_hour.get(&current) {
+                merge_per_hour_into_stats(&mut stats, ph);
+                max_timestamp = max_timestamp.max(ph.max_timestamp);
+            }
+        }
+        current += Duration::hours(1);
+    }
+
+    DataBlock {
+        kind: BlockKind::Current,
+        start,
+        end: start + Duration::hours(5),
+        unlock_timestamp: None,
+        min_timestamp: start,
+        max_timestamp,
+        stats,
+    }
+}
+
 // Create a gap block from consecutive hours
 fn create_gap_block(
     hour: &DateTime<Utc>, 
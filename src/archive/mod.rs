@@ -0,0 +1,5 @@
+mod types;
+mod utils;
+
+pub use types::*;
+pub use utils::*;
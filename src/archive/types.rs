@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{BlockLine, PerHourBlock};
+
+/// One line of the long-term archive (~/.claude-bar/archive/YYYY-MM.jsonl)
+/// Records are only ever appended: when the same key appears twice, the last one wins
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ArchiveRecord {
+    /// Hourly usage of one transcript (per-model split included)
+    Usage {
+        #[serde(flatten)]
+        source: ArchiveSource,
+        hour: Box<PerHourBlock>,
+    },
+    /// A limit message found in one transcript
+    Limit {
+        #[serde(flatten)]
+        source: ArchiveSource,
+        timestamp: DateTime<Utc>,
+        block: BlockLine,
+    },
+}
+
+/// Where an archived record comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveSource {
+    /// Claude root name
+    pub root: String,
+    /// Project folder under `projects/`
+    pub project: String,
+    /// Claude session id
+    pub session: String,
+    /// Transcript file, relative to the project folder
+    pub file: String,
+}
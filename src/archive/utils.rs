@@ -0,0 +1,143 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    archive::{ArchiveRecord, ArchiveSource},
+    cache::{CacheInfo, CachedFile, CachedFolder},
+    common::duration::round_to_hour_boundary,
+};
+
+/// Get the path to the archive directory ~/.claude-bar/archive/
+/// Returns default path if home directory not found
+pub fn get_archive_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".claude-bar")
+        .join("archive")
+}
+
+/// Append every completed hour (and limit event) not archived yet to `archive_dir`
+/// The current hour (the one `now` is in) is left for a later run, once it can no longer change
+/// Only call it before saving the cache: the watermarks it moves must be persisted,
+/// or the next run appends the same hours again
+/// Fails silently if the archive can't be written
+pub fn archive_completed_hours(cache: &mut CacheInfo, archive_dir: &Path, now: DateTime<Utc>) {
+    let cutoff = round_to_hour_boundary(now);
+    // One batch per month file, each written with a single append
+    let mut batches: BTreeMap<String, String> = BTreeMap::new();
+
+    for (root_name, cached_root) in cache.roots.iter_mut() {
+        for (folder_name, folder) in cached_root.folders.iter_mut() {
+            for (file_key, file) in folder.files.iter_mut() {
+                if file.archived_until == Some(cutoff) {
+                    continue;
+                }
+                let source = ArchiveSource {
+                    root: root_name.clone(),
+                    project: folder_name.clone(),
+                    session: file.session_key().to_string(),
+                    file: file_key.clone(),
+                };
+                for record in pending_records(file, &source, cutoff) {
+                    let month = match &record {
+                        ArchiveRecord::Usage { hour, .. } => hour.hour_start,
+                        ArchiveRecord::Limit { timestamp, .. } => *timestamp,
+                    };
+                    if let Ok(line) = serde_json::to_string(&record) {
+                        let batch = batches.entry(month.format("%Y-%m").to_string()).or_default();
+                        batch.push_str(&line);
+                        batch.push('\n');
+                    }
+                }
+                file.archived_until = Some(cutoff);
+            }
+        }
+    }
+
+    if batches.is_empty() {
+        return;
+    }
+    let _ = fs::create_dir_all(archive_dir);
+    for (month, batch) in batches {
+        let path = archive_dir.join(format!("{}.jsonl", month));
+        if let Ok(mut archive_file) = OpenOptions::new().create(true).append(true).open(path) {
+            let _ = archive_file.write_all(batch.as_bytes());
+        }
+    }
+}
+
+/// Records of `file` between its archive watermark and `cutoff`
+fn pending_records(file: &CachedFile, source: &ArchiveSource, cutoff: DateTime<Utc>) -> Vec<ArchiveRecord> {
+    let since = file.archived_until.unwrap_or(DateTime::<Utc>::MIN_UTC);
+    let in_range = |ts: &DateTime<Utc>| since <= *ts && *ts < cutoff;
+
+    let hours = file.per_hour.values()
+        .filter(|hour| in_range(&hour.hour_start))
+        .map(|hour| ArchiveRecord::Usage { source: source.clone(), hour: Box::new(hour.clone()) });
+    let limits = file.blocks.iter()
+        .filter(|(ts, _)| in_range(ts))
+        .map(|(ts, block)| ArchiveRecord::Limit { source: source.clone(), timestamp: *ts, block: block.clone() });
+
+    hours.chain(limits).collect()
+}
+
+/// Rebuild cache entries from the archive in `archive_dir`
+/// Used when the cache starts empty (first run, format change, --no-cache) so history
+/// of transcripts deleted by Claude Code is not lost. Transcripts still on disk are
+/// re-parsed from the start and overwrite the archived hours.
+pub fn load_archive(cache: &mut CacheInfo, archive_dir: &Path) {
+    let mut month_files: Vec<PathBuf> = fs::read_dir(archive_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    month_files.sort();
+
+    for path in month_files {
+        let content = fs::read_to_string(path).unwrap_or_default();
+        for record in content.lines().filter_map(|line| serde_json::from_str::<ArchiveRecord>(line).ok()) {
+            match record {
+                ArchiveRecord::Usage { source, hour } => {
+                    let archived_until = hour.hour_start + chrono::Duration::hours(1);
+                    let file = archived_file(cache, &source);
+                    file.archived_until = file.archived_until.max(Some(archived_until));
+                    file.per_hour.insert(hour.hour_start, *hour);
+                }
+                ArchiveRecord::Limit { source, timestamp, block } => {
+                    archived_file(cache, &source).blocks.insert(timestamp, block);
+                }
+            }
+        }
+    }
+}
+
+/// Get or create the cache entry an archived record belongs to
+fn archived_file<'a>(cache: &'a mut CacheInfo, source: &ArchiveSource) -> &'a mut CachedFile {
+    cache.roots.entry(source.root.clone()).or_default()
+        .folders.entry(source.project.clone()).or_insert_with(|| CachedFolder { files: HashMap::new() })
+        .files.entry(source.file.clone()).or_insert_with(|| CachedFile {
+            file_name: source.file.clone(),
+            // Parse from the start if the transcript still exists
+            cache_time: DateTime::<Utc>::UNIX_EPOCH,
+            blocks: HashMap::new(),
+            per_hour: HashMap::new(),
+            session_id: Some(source.session.clone()),
+            missing_since: None,
+            archived_until: None,
+            claude_version: None,
+            parse_errors: Vec::new(),
+            recent_events: Vec::new(),
+            open_tool_calls: HashMap::new(),
+            cache_status: Default::default(),
+            modified_time: Default::default(),
+            created_time: Default::default(),
+            size_bytes: 0,
+        })
+}
@@ -1,38 +1,87 @@
-use crate::cache::{load_cache, save_cache, set_file_info, refresh_cache, CacheInfo};
+use std::time::SystemTime;
+use chrono::Utc;
+
+use crate::{
+    archive::{archive_completed_hours, get_archive_dir, load_archive},
+    cache::{
+        file_stamp, get_cache_lock_path, get_cache_path, load_cache, save_cache, set_file_info, refresh_cache,
+        CacheInfo, FileLock, MessageIndex, LOCK_TIMEOUT,
+    },
+    config::{ClaudeRoot, ConfigInfo, MissingFilePolicy},
+};
 
 pub struct CacheManager {
     cache: CacheInfo,
-    base_path: String,
+    roots: Vec<ClaudeRoot>,
+    missing_policy: MissingFilePolicy,
+    /// Modification time of the cache file when we read it (None if it didn't exist)
+    loaded_stamp: Option<SystemTime>,
 }
 
 impl CacheManager {
-    pub fn new(base_path: &str, no_cache: bool) -> Self {
-        let cache = match no_cache {
+    pub fn new(config: &ConfigInfo, no_cache: bool) -> Self {
+        // Stamp is taken before reading so a concurrent save is never mistaken for ours
+        let loaded_stamp = file_stamp(&get_cache_path());
+        let mut cache = match no_cache {
             true => CacheInfo::default(),
             false => load_cache(),
         };
-        let mut cm = Self { cache, base_path: base_path.to_string() };
+        // Fresh cache: recover history of transcripts that no longer exist
+        if cache.roots.is_empty() {
+            load_archive(&mut cache, &get_archive_dir());
+        }
+        let mut cm = Self {
+            cache,
+            roots: config.claude_data_path.roots(),
+            missing_policy: config.missing_files,
+            loaded_stamp,
+        };
         cm.set_file_info();
         
         cm
     }
 
     pub fn set_file_info(&mut self) {
-        set_file_info(&mut self.cache, &self.base_path);
+        for root in &self.roots {
+            let cached_root = self.cache.roots.entry(root.name.clone()).or_default();
+            set_file_info(cached_root, &root.path, self.missing_policy);
+        }
     }
 
     pub fn get_cache(&self) -> &CacheInfo {
         &self.cache
     }
 
-    pub fn save(&self) {
+    /// Configured Claude roots, in config order
+    pub fn roots(&self) -> &[ClaudeRoot] {
+        &self.roots
+    }
+
+    /// Archive completed hours and save the cache, under an advisory lock
+    /// Several `prompt` invocations can run in parallel: if the lock can't be taken
+    /// in time, or another process saved newer data since we loaded, skip both. The
+    /// archive watermarks are saved with the cache, so hours are only appended once.
+    pub fn save(&mut self) {
+        let Some(_lock) = FileLock::acquire(&get_cache_lock_path(), LOCK_TIMEOUT) else {
+            return;
+        };
+        if file_stamp(&get_cache_path()) > self.loaded_stamp {
+            return;
+        }
+        archive_completed_hours(&mut self.cache, &get_archive_dir(), Utc::now());
         save_cache(&self.cache);
     }
 
     /// Refresh all files marked as NeedsRefresh in the cache
     /// Updates cache entries in memory without saving to disk
-    pub fn refresh_cache(&mut self) {        
-        refresh_cache(&mut self.cache, &self.base_path);
+    /// Each API response is counted once across all roots (see `MessageIndex`)
+    pub fn refresh_cache(&mut self) {
+        let mut index = MessageIndex::build(&self.cache);
+        for root in &self.roots {
+            if let Some(cached_root) = self.cache.roots.get_mut(&root.name) {
+                refresh_cache(cached_root, &root.path, &mut index);
+            }
+        }
     }
 }
 
@@ -40,4 +89,4 @@ impl Drop for CacheManager {
     fn drop(&mut self) {
         //let _ = save_cache(); // Ignore les erreurs dans Drop
     }
-}
\ No newline at end of file
+}
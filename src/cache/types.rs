@@ -1,15 +1,33 @@
 use std::collections::HashMap;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::{cache::resolve_reset_text, common::time};
+
 // represent the cache information in .claude_bar/cache.json
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct CacheInfo {
-    pub folders: HashMap<String, CachedFolder>
+    /// Cached data namespaced by Claude root name
+    pub roots: HashMap<String, CachedRoot>
 }
 impl Default for CacheInfo {
-    fn default() -> Self { CacheInfo { folders: HashMap::new() } }
+    fn default() -> Self { CacheInfo { roots: HashMap::new() } }
+}
+
+impl CacheInfo {
+    /// Iterate over cached roots, restricted to `root` when given
+    pub fn selected_roots<'a>(&'a self, root: Option<&'a str>) -> impl Iterator<Item = (&'a String, &'a CachedRoot)> {
+        self.roots
+            .iter()
+            .filter(move |(name, _)| root.is_none_or(|r| r == name.as_str()))
+    }
+}
+
+/// Project folders found under one Claude root's `projects/` directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedRoot {
+    pub folders: HashMap<String, CachedFolder>,
 }
 
 
@@ -20,12 +38,35 @@ pub struct CachedFolder {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachedFile {
+    /// Path relative to the project folder (nested for subagent transcripts)
     pub file_name: String,
     pub cache_time: DateTime<Utc>,   //Use as cache date
     /// Map of limit/unlock events keyed by block timestamp
     pub blocks: HashMap<DateTime<Utc>, BlockLine>,
     /// Map of hourly usage summaries (hour_start -> PerHourBlock) for O(1) lookup
     pub per_hour: HashMap<DateTime<Utc>, PerHourBlock>,
+    /// Claude session id read from the transcript (used to follow moved files)
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// When the transcript was first found missing on disk (None while it exists)
+    #[serde(default)]
+    pub missing_since: Option<DateTime<Utc>>,
+    /// Hours before this point have been written to the long-term archive
+    #[serde(default)]
+    pub archived_until: Option<DateTime<Utc>>,
+    /// Claude Code version of the newest entry (from the `version` field)
+    #[serde(default)]
+    pub claude_version: Option<String>,
+    /// Newest parse errors, for `debug parse` (at most MAX_PARSE_ERRORS)
+    #[serde(default)]
+    pub parse_errors: Vec<String>,
+    /// Messages of the last `RECENT_EVENT_HOURS` hours at full resolution, oldest first
+    #[serde(default)]
+    pub recent_events: Vec<UsageEvent>,
+    /// Tool calls of the last `RECENT_EVENT_HOURS` hours whose result a later refresh
+    /// may parse, by tool_use id, so the errors of results are counted across refreshes
+    #[serde(default)]
+    pub open_tool_calls: HashMap<String, ToolCall>,
     #[serde(skip)]
     pub cache_status: CacheStatus,
     #[serde(skip)]
@@ -36,13 +77,110 @@ pub struct CachedFile {
     pub size_bytes: u64,
 }
 
+impl CachedFile {
+    /// Session this transcript belongs to: the parent session for nested (subagent)
+    /// transcripts, else its session id, falling back to the file stem
+    /// (Claude Code names transcripts `<session id>.jsonl`)
+    pub fn session_key(&self) -> &str {
+        self.parent_session()
+            .or(self.session_id.as_deref())
+            .unwrap_or_else(|| self.file_stem())
+    }
+
+    /// Parent session of a nested transcript (`<session id>/subagents/agent-x.jsonl`)
+    /// `file_name` is the path relative to the project folder
+    pub fn parent_session(&self) -> Option<&str> {
+        self.file_name.split_once('/').map(|(parent, _)| parent)
+    }
+
+    /// Identifies the transcript independently of its location (used to follow moved files)
+    /// Subagent transcripts (nested, or `agent-*.jsonl`) share their parent's session id
+    pub fn transcript_key(&self) -> String {
+        let stem = self.file_stem();
+        match self.parent_session().is_some() || stem.starts_with("agent-") {
+            true => format!("{}/{}", self.session_key(), stem),
+            false => self.session_key().to_string(),
+        }
+    }
+
+    /// File name without directories and `.jsonl` extension
+    fn file_stem(&self) -> &str {
+        let name = self.file_name.rsplit('/').next().unwrap_or(&self.file_name);
+        name.strip_suffix(".jsonl").unwrap_or(name)
+    }
+}
+
+/// Hours of messages kept in `CachedFile::recent_events` (older usage is only kept per hour)
+pub const RECENT_EVENT_HOURS: i64 = 6;
+
+/// A single message, for minute-level analysis of the current window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageEvent {
+    pub timestamp: DateTime<Utc>,
+    pub output_tokens: u32,
+    /// Model of an assistant message (None for user messages)
+    pub model: Option<String>,
+}
+
+/// A tool call waiting for its result to be parsed (see `CachedFile::open_tool_calls`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    pub timestamp: DateTime<Utc>,
+    /// The error of its result was added to the hour of the call
+    #[serde(default)]
+    pub error_counted: bool,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockLine {
-    /// Timestamp when the block was lifted/reset (if available)
-    pub unlock_timestamp: Option<DateTime<Utc>>,
-    /// Human-readable reset time (e.g. "5pm", "2h30m")
+    /// Human-readable reset time (e.g. "5pm", "in 2h30m"), see `unlock_timestamp`
     pub reset_text: String,
+    /// Which cap was hit (5-hour window or a weekly cap)
+    #[serde(default)]
+    pub kind: LimitKind,
+}
+
+impl BlockLine {
+    /// When the limit lifts, for a message sent at `block_time`
+    /// Resolved on each read rather than cached: times without a zone are read in
+    /// the configured timezone, which may change after the message was parsed
+    pub fn unlock_timestamp(&self, block_time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        resolve_reset_text(&self.reset_text, block_time, time::settings().reset_zone)
+    }
+}
+
+/// Usage cap a limit message refers to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LimitKind {
+    #[default]
+    FiveHour,
+    Weekly,
+    WeeklyOpus,
+}
+
+impl LimitKind {
+    /// Classify a limit message, e.g. "Opus weekly limit reached ∙ resets Mon 9am" -> WeeklyOpus
+    pub fn from_message(message: &str) -> Self {
+        let lower = message.to_lowercase();
+        match (lower.contains("weekly"), lower.contains("opus")) {
+            (true, true) => LimitKind::WeeklyOpus,
+            (true, false) => LimitKind::Weekly,
+            _ => LimitKind::FiveHour,
+        }
+    }
+
+    pub fn is_weekly(&self) -> bool {
+        !matches!(self, LimitKind::FiveHour)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LimitKind::FiveHour => "5-hour",
+            LimitKind::Weekly => "Weekly",
+            LimitKind::WeeklyOpus => "Opus weekly",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +197,10 @@ pub struct PerHourBlock {
     pub input_tokens: u32,
     /// Total output tokens used in this hour
     pub output_tokens: u32,
+    /// Output tokens per 5-minute slot of the hour (12 slots, empty for hours
+    /// restored from an older archive)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output_by_5min: Vec<u32>,
     /// Total cache creation tokens in this hour
     pub cache_creation_tokens: u32,
     /// Total cache read tokens in this hour
@@ -71,6 +213,189 @@ pub struct PerHourBlock {
     pub total_content_length: u64,
     /// Number of entries processed in this hour
     pub entry_count: u32,
+    /// Token usage split by model name
+    #[serde(default)]
+    pub models: HashMap<String, ModelUsage>,
+    /// Part of the usage above spent by subagents (Task tool, `isSidechain` entries)
+    #[serde(default)]
+    pub sidechain: ModelUsage,
+    /// Web searches run by the API (server_tool_use.web_search_requests)
+    #[serde(default)]
+    pub web_search_requests: u32,
+    /// Messages by service tier ("standard", "priority", "batch")
+    #[serde(default)]
+    pub service_tiers: HashMap<String, u32>,
+    /// Tool calls by tool name (Bash, Edit, mcp__server__tool...)
+    #[serde(default)]
+    pub tools: HashMap<String, ToolStats>,
+    /// Bash calls by program (e.g. "git commit", "ls")
+    #[serde(default)]
+    pub bash_commands: HashMap<String, u32>,
+    /// Edit/Write calls by file path
+    #[serde(default)]
+    pub edited_files: HashMap<String, u32>,
+    /// Line counters of the parser for this hour
+    #[serde(default)]
+    pub parse_health: ParseHealth,
+    /// Entries whose API response was already counted (streamed or copied messages)
+    #[serde(default)]
+    pub duplicate_entries: u32,
+    /// Keys of the API responses and tool calls counted in this hour (see `MessageIndex`)
+    /// Archived too: hours restored from the archive keep claiming their messages
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub message_ids: Vec<u64>,
+}
+
+/// 5-minute slots per hour in `PerHourBlock::output_by_5min`
+pub const SLOTS_PER_HOUR: usize = 12;
+
+impl PerHourBlock {
+    /// Empty hour block, with `timestamp` as its first entry
+    pub fn new(hour_start: DateTime<Utc>, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            hour_start,
+            hour_end: hour_start + Duration::hours(1) - Duration::seconds(1),
+            min_timestamp: timestamp,
+            max_timestamp: timestamp,
+            input_tokens: 0,
+            output_tokens: 0,
+            output_by_5min: Vec::new(),
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            assistant_messages: 0,
+            user_messages: 0,
+            total_content_length: 0,
+            entry_count: 0,
+            models: HashMap::new(),
+            sidechain: ModelUsage::default(),
+            web_search_requests: 0,
+            service_tiers: HashMap::new(),
+            tools: HashMap::new(),
+            bash_commands: HashMap::new(),
+            edited_files: HashMap::new(),
+            parse_health: ParseHealth::default(),
+            duplicate_entries: 0,
+            message_ids: Vec::new(),
+        }
+    }
+
+    /// Add the usage of `other`, an hour block of the same hour
+    /// (message ids are left out: aggregates are never deduplicated again)
+    pub fn merge(&mut self, other: &PerHourBlock) {
+        self.min_timestamp = self.min_timestamp.min(other.min_timestamp);
+        self.max_timestamp = self.max_timestamp.max(other.max_timestamp);
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        if self.output_by_5min.len() < other.output_by_5min.len() {
+            self.output_by_5min.resize(other.output_by_5min.len(), 0);
+        }
+        for (slot, tokens) in self.output_by_5min.iter_mut().zip(&other.output_by_5min) {
+            *slot += tokens;
+        }
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.assistant_messages += other.assistant_messages;
+        self.user_messages += other.user_messages;
+        self.total_content_length += other.total_content_length;
+        self.entry_count += other.entry_count;
+        self.sidechain.merge(&other.sidechain);
+        self.parse_health.merge(&other.parse_health);
+        self.web_search_requests += other.web_search_requests;
+        for (tier, count) in &other.service_tiers {
+            *self.service_tiers.entry(tier.clone()).or_default() += count;
+        }
+        for (tool, stats) in &other.tools {
+            self.tools.entry(tool.clone()).or_default().merge(stats);
+        }
+        for (program, count) in &other.bash_commands {
+            *self.bash_commands.entry(program.clone()).or_default() += count;
+        }
+        for (path, count) in &other.edited_files {
+            *self.edited_files.entry(path.clone()).or_default() += count;
+        }
+        self.duplicate_entries += other.duplicate_entries;
+        for (model, usage) in &other.models {
+            self.models.entry(model.clone()).or_default().merge(usage);
+        }
+    }
+
+    /// Add output tokens to the 5-minute slot of `timestamp`
+    pub fn add_slot_output(&mut self, timestamp: DateTime<Utc>, output_tokens: u32) {
+        if self.output_by_5min.is_empty() {
+            self.output_by_5min = vec![0; SLOTS_PER_HOUR];
+        }
+        let slot = ((timestamp - self.hour_start).num_minutes().max(0) / 5) as usize;
+        self.output_by_5min[slot.min(SLOTS_PER_HOUR - 1)] += output_tokens;
+    }
+}
+
+/// Token usage of a subset of an hour (a single model, subagents)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cache_creation_tokens: u32,
+    pub cache_read_tokens: u32,
+    /// Number of assistant messages in this subset
+    pub messages: u32,
+}
+
+impl ModelUsage {
+    pub fn merge(&mut self, other: &ModelUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.messages += other.messages;
+    }
+}
+
+/// Line counters of the transcript parser, to notice transcript format changes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParseHealth {
+    /// Lines read as transcript entries or summaries
+    pub parsed_lines: u32,
+    /// Valid JSON lines of another shape (new entry types, schema changes)
+    pub unknown_lines: u32,
+    /// Lines that are not valid JSON
+    pub failed_lines: u32,
+    /// Parsed lines carrying token usage
+    pub usage_lines: u32,
+    /// Unparsed lines carrying token usage: usage missing from reports
+    pub lost_usage_lines: u32,
+}
+
+impl ParseHealth {
+    pub fn merge(&mut self, other: &ParseHealth) {
+        self.parsed_lines += other.parsed_lines;
+        self.unknown_lines += other.unknown_lines;
+        self.failed_lines += other.failed_lines;
+        self.usage_lines += other.usage_lines;
+        self.lost_usage_lines += other.lost_usage_lines;
+    }
+
+    /// Share of the lines with usage that could not be parsed, in percent
+    pub fn lost_usage_percent(&self) -> f64 {
+        match self.usage_lines + self.lost_usage_lines {
+            0 => 0.0,
+            total => 100.0 * self.lost_usage_lines as f64 / total as f64,
+        }
+    }
+}
+
+/// Calls of a single tool within an hour
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolStats {
+    pub calls: u32,
+    /// Calls whose tool_result was flagged `is_error`
+    pub errors: u32,
+}
+
+impl ToolStats {
+    pub fn merge(&mut self, other: &ToolStats) {
+        self.calls += other.calls;
+        self.errors += other.errors;
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -78,6 +403,7 @@ pub enum CacheStatus {
     Fresh,           // File in cache and up-to-date
     NeedsRefresh,    // File modified since cache date
     NotInCache,      // File not in cache yet
+    Missing,         // File in cache but no longer on disk
 }
 impl Default for CacheStatus {
     fn default() -> Self { CacheStatus::NotInCache }
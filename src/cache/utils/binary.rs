@@ -0,0 +1,459 @@
+use std::{collections::HashMap, hash::Hash};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::cache::{BlockLine, CacheInfo, CachedFile, CachedFolder, CachedRoot, LimitKind, ModelUsage, ParseHealth, PerHourBlock, ToolCall, ToolStats, UsageEvent};
+
+/// Magic bytes at the start of ~/.claude-bar/cache.bin
+pub const CACHE_MAGIC: &[u8; 4] = b"CBAR";
+/// Bump when a released encoding of any cached type changes:
+/// caches written with another version are discarded and rebuilt
+pub const CACHE_SCHEMA_VERSION: u16 = 1;
+
+/// Encode the cache with its header (magic + schema version)
+pub fn encode_cache(cache: &CacheInfo) -> Vec<u8> {
+    let mut enc = Encoder::default();
+    enc.buf.extend_from_slice(CACHE_MAGIC);
+    enc.buf.extend_from_slice(&CACHE_SCHEMA_VERSION.to_le_bytes());
+    cache.encode(&mut enc);
+    enc.buf
+}
+
+/// Decode a cache, None if the header doesn't match or the data is truncated
+pub fn decode_cache(bytes: &[u8]) -> Option<CacheInfo> {
+    let header_len = CACHE_MAGIC.len() + 2;
+    if bytes.len() < header_len || &bytes[..CACHE_MAGIC.len()] != CACHE_MAGIC {
+        return None;
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != CACHE_SCHEMA_VERSION {
+        return None;
+    }
+
+    let mut dec = Decoder { buf: &bytes[header_len..], pos: 0 };
+    let cache = CacheInfo::decode(&mut dec)?;
+    // Trailing bytes mean the writer and reader disagree on the layout
+    (dec.pos == dec.buf.len()).then_some(cache)
+}
+
+/// Append-only byte buffer with LEB128 varints
+#[derive(Default)]
+pub struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn u64(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    pub fn i64(&mut self, value: i64) {
+        // zigzag so small negative numbers stay small
+        self.u64(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.buf.push(value as u8);
+    }
+
+    pub fn str(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.buf.extend_from_slice(value.as_bytes());
+    }
+
+    pub fn time(&mut self, value: DateTime<Utc>) {
+        self.i64(value.timestamp());
+        self.u64(value.timestamp_subsec_nanos() as u64);
+    }
+}
+
+/// Cursor over encoded bytes, every read returns None past the end
+pub struct Decoder<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Decoder<'_> {
+    pub fn u64(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.buf.get(self.pos)?;
+            self.pos += 1;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        u32::try_from(self.u64()?).ok()
+    }
+
+    pub fn i64(&mut self) -> Option<i64> {
+        let raw = self.u64()?;
+        Some(((raw >> 1) as i64) ^ -((raw & 1) as i64))
+    }
+
+    pub fn bool(&mut self) -> Option<bool> {
+        let byte = *self.buf.get(self.pos)?;
+        self.pos += 1;
+        Some(byte != 0)
+    }
+
+    pub fn str(&mut self) -> Option<String> {
+        let len = self.count()?;
+        let bytes = self.buf.get(self.pos..self.pos + len)?;
+        self.pos += len;
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+
+    pub fn time(&mut self) -> Option<DateTime<Utc>> {
+        let secs = self.i64()?;
+        let nanos = self.u32()?;
+        DateTime::from_timestamp(secs, nanos)
+    }
+
+    /// Read a collection length, rejecting lengths larger than the remaining input
+    pub fn count(&mut self) -> Option<usize> {
+        let len = usize::try_from(self.u64()?).ok()?;
+        (len <= self.buf.len() - self.pos).then_some(len)
+    }
+}
+
+/// Types stored in the binary cache
+pub trait Binary: Sized {
+    fn encode(&self, enc: &mut Encoder);
+    fn decode(dec: &mut Decoder) -> Option<Self>;
+}
+
+impl Binary for String {
+    fn encode(&self, enc: &mut Encoder) { enc.str(self) }
+    fn decode(dec: &mut Decoder) -> Option<Self> { dec.str() }
+}
+
+impl Binary for u32 {
+    fn encode(&self, enc: &mut Encoder) { enc.u64(*self as u64) }
+    fn decode(dec: &mut Decoder) -> Option<Self> { dec.u32() }
+}
+
+impl Binary for u64 {
+    fn encode(&self, enc: &mut Encoder) { enc.u64(*self) }
+    fn decode(dec: &mut Decoder) -> Option<Self> { dec.u64() }
+}
+
+impl Binary for DateTime<Utc> {
+    fn encode(&self, enc: &mut Encoder) { enc.time(*self) }
+    fn decode(dec: &mut Decoder) -> Option<Self> { dec.time() }
+}
+
+impl<T: Binary> Binary for Option<T> {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.bool(self.is_some());
+        if let Some(value) = self {
+            value.encode(enc);
+        }
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        match dec.bool()? {
+            true => Some(Some(T::decode(dec)?)),
+            false => Some(None),
+        }
+    }
+}
+
+impl<T: Binary> Binary for Vec<T> {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.u64(self.len() as u64);
+        for value in self {
+            value.encode(enc);
+        }
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        let len = dec.count()?;
+        (0..len).map(|_| T::decode(dec)).collect()
+    }
+}
+
+impl<K: Binary + Eq + Hash, V: Binary> Binary for HashMap<K, V> {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.u64(self.len() as u64);
+        for (key, value) in self {
+            key.encode(enc);
+            value.encode(enc);
+        }
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        let len = dec.count()?;
+        let mut map = HashMap::with_capacity(len);
+        for _ in 0..len {
+            let key = K::decode(dec)?;
+            map.insert(key, V::decode(dec)?);
+        }
+        Some(map)
+    }
+}
+
+impl Binary for CacheInfo {
+    fn encode(&self, enc: &mut Encoder) {
+        self.roots.encode(enc);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        Some(Self { roots: HashMap::decode(dec)? })
+    }
+}
+
+impl Binary for CachedRoot {
+    fn encode(&self, enc: &mut Encoder) {
+        self.folders.encode(enc);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        Some(Self { folders: HashMap::decode(dec)? })
+    }
+}
+
+impl Binary for CachedFolder {
+    fn encode(&self, enc: &mut Encoder) {
+        self.files.encode(enc);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        Some(Self { files: HashMap::decode(dec)? })
+    }
+}
+
+impl Binary for CachedFile {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.str(&self.file_name);
+        enc.time(self.cache_time);
+        // Kept so unchanged files are seen as Fresh and not re-read on the next run
+        enc.time(self.modified_time);
+        self.blocks.encode(enc);
+        // Hour records carry their own key (hour_start), store them as a plain list
+        enc.u64(self.per_hour.len() as u64);
+        for hour in self.per_hour.values() {
+            hour.encode(enc);
+        }
+        self.session_id.encode(enc);
+        self.missing_since.encode(enc);
+        self.archived_until.encode(enc);
+        self.claude_version.encode(enc);
+        self.parse_errors.encode(enc);
+        self.recent_events.encode(enc);
+        self.open_tool_calls.encode(enc);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        let file_name = dec.str()?;
+        let cache_time = dec.time()?;
+        let modified_time = dec.time()?;
+        let blocks = HashMap::decode(dec)?;
+        let hours = dec.count()?;
+        let mut per_hour = HashMap::with_capacity(hours);
+        for _ in 0..hours {
+            let hour = PerHourBlock::decode(dec)?;
+            per_hour.insert(hour.hour_start, hour);
+        }
+        Some(Self {
+            file_name,
+            cache_time,
+            blocks,
+            per_hour,
+            session_id: Option::decode(dec)?,
+            missing_since: Option::decode(dec)?,
+            archived_until: Option::decode(dec)?,
+            claude_version: Option::decode(dec)?,
+            parse_errors: Vec::decode(dec)?,
+            recent_events: Vec::decode(dec)?,
+            open_tool_calls: HashMap::decode(dec)?,
+            cache_status: Default::default(),
+            modified_time,
+            created_time: Default::default(),
+            size_bytes: 0,
+        })
+    }
+}
+
+impl Binary for UsageEvent {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.time(self.timestamp);
+        enc.u64(self.output_tokens as u64);
+        self.model.encode(enc);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        Some(Self {
+            timestamp: dec.time()?,
+            output_tokens: dec.u32()?,
+            model: Option::decode(dec)?,
+        })
+    }
+}
+
+impl Binary for ToolCall {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.str(&self.name);
+        enc.time(self.timestamp);
+        enc.bool(self.error_counted);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        Some(Self {
+            name: dec.str()?,
+            timestamp: dec.time()?,
+            error_counted: dec.bool()?,
+        })
+    }
+}
+
+impl Binary for BlockLine {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.str(&self.reset_text);
+        self.kind.encode(enc);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        Some(Self {
+            reset_text: dec.str()?,
+            kind: LimitKind::decode(dec)?,
+        })
+    }
+}
+
+impl Binary for LimitKind {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.u64(*self as u64);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        match dec.u64()? {
+            0 => Some(LimitKind::FiveHour),
+            1 => Some(LimitKind::Weekly),
+            2 => Some(LimitKind::WeeklyOpus),
+            _ => None,
+        }
+    }
+}
+
+/// Hour records are the bulk of the cache: the hour is stored as an hour index,
+/// hour_end is derived, and min/max timestamps as millisecond offsets into the hour
+impl Binary for PerHourBlock {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.i64(self.hour_start.timestamp() / 3600);
+        enc.i64((self.min_timestamp - self.hour_start).num_milliseconds());
+        enc.i64((self.max_timestamp - self.hour_start).num_milliseconds());
+        enc.u64(self.input_tokens as u64);
+        enc.u64(self.output_tokens as u64);
+        self.output_by_5min.encode(enc);
+        enc.u64(self.cache_creation_tokens as u64);
+        enc.u64(self.cache_read_tokens as u64);
+        enc.u64(self.assistant_messages as u64);
+        enc.u64(self.user_messages as u64);
+        enc.u64(self.total_content_length);
+        enc.u64(self.entry_count as u64);
+        self.models.encode(enc);
+        self.sidechain.encode(enc);
+        enc.u64(self.web_search_requests as u64);
+        self.service_tiers.encode(enc);
+        self.tools.encode(enc);
+        self.bash_commands.encode(enc);
+        self.edited_files.encode(enc);
+        self.parse_health.encode(enc);
+        enc.u64(self.duplicate_entries as u64);
+        self.message_ids.encode(enc);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        let hour_start = DateTime::from_timestamp(dec.i64()?.checked_mul(3600)?, 0)?;
+        let min_timestamp = hour_start + Duration::milliseconds(dec.i64()?);
+        let max_timestamp = hour_start + Duration::milliseconds(dec.i64()?);
+        Some(Self {
+            hour_start,
+            hour_end: hour_start + Duration::hours(1) - Duration::seconds(1),
+            min_timestamp,
+            max_timestamp,
+            input_tokens: dec.u32()?,
+            output_tokens: dec.u32()?,
+            output_by_5min: Vec::decode(dec)?,
+            cache_creation_tokens: dec.u32()?,
+            cache_read_tokens: dec.u32()?,
+            assistant_messages: dec.u32()?,
+            user_messages: dec.u32()?,
+            total_content_length: dec.u64()?,
+            entry_count: dec.u32()?,
+            models: HashMap::decode(dec)?,
+            sidechain: ModelUsage::decode(dec)?,
+            web_search_requests: dec.u32()?,
+            service_tiers: HashMap::decode(dec)?,
+            tools: HashMap::decode(dec)?,
+            bash_commands: HashMap::decode(dec)?,
+            edited_files: HashMap::decode(dec)?,
+            parse_health: ParseHealth::decode(dec)?,
+            duplicate_entries: dec.u32()?,
+            message_ids: Vec::decode(dec)?,
+        })
+    }
+}
+
+impl Binary for ModelUsage {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.u64(self.input_tokens as u64);
+        enc.u64(self.output_tokens as u64);
+        enc.u64(self.cache_creation_tokens as u64);
+        enc.u64(self.cache_read_tokens as u64);
+        enc.u64(self.messages as u64);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        Some(Self {
+            input_tokens: dec.u32()?,
+            output_tokens: dec.u32()?,
+            cache_creation_tokens: dec.u32()?,
+            cache_read_tokens: dec.u32()?,
+            messages: dec.u32()?,
+        })
+    }
+}
+
+impl Binary for ToolStats {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.u64(self.calls as u64);
+        enc.u64(self.errors as u64);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        Some(Self {
+            calls: dec.u32()?,
+            errors: dec.u32()?,
+        })
+    }
+}
+
+impl Binary for ParseHealth {
+    fn encode(&self, enc: &mut Encoder) {
+        enc.u64(self.parsed_lines as u64);
+        enc.u64(self.unknown_lines as u64);
+        enc.u64(self.failed_lines as u64);
+        enc.u64(self.usage_lines as u64);
+        enc.u64(self.lost_usage_lines as u64);
+    }
+
+    fn decode(dec: &mut Decoder) -> Option<Self> {
+        Some(Self {
+            parsed_lines: dec.u32()?,
+            unknown_lines: dec.u32()?,
+            failed_lines: dec.u32()?,
+            usage_lines: dec.u32()?,
+            lost_usage_lines: dec.u32()?,
+        })
+    }
+}
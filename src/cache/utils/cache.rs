@@ -1,33 +1,51 @@
-use std::{collections::{HashMap, hash_map::Entry}, fs, path::{Path, PathBuf}};
+use std::{collections::{HashMap, HashSet, hash_map::Entry}, fs, path::{Path, PathBuf}};
 use chrono::{DateTime, Utc};
 
-use crate::cache::{CacheInfo, CacheStatus, CachedFile, CachedFolder,utils::parse::refresh_single_file};
+use crate::{
+    cache::{
+        CacheInfo, CacheStatus, CachedFile, CachedFolder, CachedRoot, MessageIndex,
+        utils::{binary::{decode_cache, encode_cache}, lock::write_atomic, parse::refresh_single_file},
+    },
+    config::MissingFilePolicy,
+};
 
-/// Get the path to cache.json in ~/.claude-bar/ directory
+/// Get the path to cache.bin in ~/.claude-bar/ directory
 /// Returns default path if home directory not found
-fn get_cache_path() -> PathBuf {
+pub fn get_cache_path() -> PathBuf {
     dirs::home_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join(".claude-bar")
-        .join("cache.json")
+        .join("cache.bin")
 }
 
-/// Load cache from ~/.claude-bar/cache.json
+/// Pre-binary JSON cache, removed on the first binary save
+fn get_legacy_cache_path() -> PathBuf {
+    get_cache_path().with_file_name("cache.json")
+}
+
+/// Get the path to the lock file guarding cache writes
+pub fn get_cache_lock_path() -> PathBuf {
+    get_cache_path().with_file_name("cache.lock")
+}
+
+/// Load cache from ~/.claude-bar/cache.bin
+/// A missing, corrupted or older-schema cache yields an empty cache (full rebuild)
 pub fn load_cache() -> CacheInfo {
     let cache_path = get_cache_path();
     
-    let content = fs::read_to_string(cache_path)
+    let content = fs::read(cache_path)
         .unwrap_or_default();
     
     if content.is_empty() {
         return CacheInfo::default();
     }
     
-    serde_json::from_str(&content)
+    decode_cache(&content)
         .unwrap_or_default()
 }
 
-/// Save cache to ~/.claude-bar/cache.json
+/// Save cache to ~/.claude-bar/cache.bin
+/// Written to a temp file then renamed, so readers never see a truncated cache
 /// Fails silently if cannot save
 pub fn save_cache(cache: &CacheInfo) {
     let cache_path = get_cache_path();
@@ -37,24 +55,53 @@ pub fn save_cache(cache: &CacheInfo) {
         let _ = fs::create_dir_all(parent);
     }
     
-    if let Ok(content) = serde_json::to_string_pretty(cache) {
-        let _ = fs::write(cache_path, content);
+    if write_atomic(&cache_path, &encode_cache(cache)).is_ok() {
+        let _ = fs::remove_file(get_legacy_cache_path());
     }
 }
 
+/// Export cache as pretty JSON (for inspection or external tools)
+pub fn export_cache_json(cache: &CacheInfo) -> String {
+    serde_json::to_string_pretty(cache).unwrap_or_default()
+}
+
 /// Update cache with current folder structure from base_path
-/// Creates new entries or updates existing ones
+/// Creates new entries or updates existing ones, then reconciles
+/// entries whose file disappeared according to `missing_policy`
+/// A root whose `projects` directory can't be read is left untouched
 /// base_path should be the Claude data directory (e.g. ~/.claude)
 /// This function automatically scans the /projects subdirectory
-pub fn set_file_info(cache: &mut CacheInfo, base_path: &str) {
+pub fn set_file_info(cache: &mut CachedRoot, base_path: &str, missing_policy: MissingFilePolicy) {
     let projects_path = format!("{}/projects", base_path);
     let path: &Path = Path::new(&projects_path);
 
-    let dir_entries = fs::read_dir(path)
-        .into_iter()
-        .flatten()
+    let Ok(read_dir) = fs::read_dir(path) else {
+        // Root unreachable (unmounted, not created yet): nothing is known about its files,
+        // keep their history as is rather than flagging or pruning all of it
+        for folder in cache.folders.values_mut() {
+            for file in folder.files.values_mut() {
+                file.cache_status = CacheStatus::Fresh;
+            }
+        }
+        return;
+    };
+
+    // Anything not found by the scan below stays Missing
+    for folder in cache.folders.values_mut() {
+        for file in folder.files.values_mut() {
+            file.cache_status = CacheStatus::Missing;
+        }
+    }
+
+    let dir_entries: Vec<_> = read_dir
         .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.path().is_dir());
+        .filter(|entry| entry.path().is_dir())
+        .collect();
+
+    // Project folders are only scanned as themselves, never through a symlink in another one
+    let mut visited: HashSet<PathBuf> = dir_entries.iter()
+        .filter_map(|entry| fs::canonicalize(entry.path()).ok())
+        .collect();
     
     for entry in dir_entries {
         let folder_name = entry.file_name().to_string_lossy().to_string();
@@ -66,21 +113,80 @@ pub fn set_file_info(cache: &mut CacheInfo, base_path: &str) {
             }
         });
         
-        scan_folder(cached_folder, &entry.path());
+        scan_folder(cached_folder, &entry.path(), &mut visited);
+    }
+
+    reconcile_missing(cache, missing_policy);
+}
+
+/// Handle cached files that are no longer on disk:
+/// - a new file with the same session id is the same transcript moved/renamed:
+///   its history is carried over to the new location
+/// - the others are kept (flagged with `missing_since`) or pruned, per policy
+fn reconcile_missing(cache: &mut CachedRoot, missing_policy: MissingFilePolicy) {
+    // Keyed by transcript_key: nested subagent transcripts share their parent's session id
+    let mut missing: HashMap<String, (String, String)> = HashMap::new();
+    let mut new_files: Vec<(String, String, String)> = Vec::new();
+
+    for (folder_name, folder) in &cache.folders {
+        for (file_name, file) in &folder.files {
+            match file.cache_status {
+                CacheStatus::Missing => {
+                    missing.insert(file.transcript_key(), (folder_name.clone(), file_name.clone()));
+                }
+                CacheStatus::NotInCache => {
+                    new_files.push((file.transcript_key(), folder_name.clone(), file_name.clone()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Renames: move the old entry (history + parse position) onto the new file
+    for (session, folder_name, file_name) in new_files {
+        let Some((old_folder, old_file)) = missing.remove(&session) else {
+            continue;
+        };
+        let Some(mut moved) = cache.folders.get_mut(&old_folder).and_then(|f| f.files.remove(&old_file)) else {
+            continue;
+        };
+        if let Some(new_entry) = cache.folders.get_mut(&folder_name).and_then(|f| f.files.get_mut(&file_name)) {
+            moved.file_name = new_entry.file_name.clone();
+            moved.modified_time = new_entry.modified_time;
+            moved.created_time = new_entry.created_time;
+            moved.size_bytes = new_entry.size_bytes;
+            moved.missing_since = None;
+            moved.cache_status = CacheStatus::NeedsRefresh;
+            *new_entry = moved;
+        }
+    }
+
+    let now = Utc::now();
+    for folder in cache.folders.values_mut() {
+        match missing_policy {
+            MissingFilePolicy::Retain => {
+                for file in folder.files.values_mut() {
+                    if matches!(file.cache_status, CacheStatus::Missing) {
+                        file.missing_since.get_or_insert(now);
+                    } else {
+                        file.missing_since = None;
+                    }
+                }
+            }
+            MissingFilePolicy::Prune => {
+                folder.files.retain(|_, file| !matches!(file.cache_status, CacheStatus::Missing));
+            }
+        }
     }
+    cache.folders.retain(|_, folder| !folder.files.is_empty());
 }
 
-/// Scan a folder and return list of cached files with updated status
-fn scan_folder(cached_folder: &mut CachedFolder, folder_path: &Path) {
-   let files = fs::read_dir(folder_path)
-       .into_iter()
-       .flatten()
-       .filter_map(|entry| entry.ok())
-       .filter(|entry| entry.path().is_file());
+/// Scan a project folder (recursively) and update its cached files status
+fn scan_folder(cached_folder: &mut CachedFolder, folder_path: &Path, visited: &mut HashSet<PathBuf>) {
+   let mut files = Vec::new();
+   collect_transcripts(folder_path, "", visited, &mut files);
    
-   for entry in files {
-        let file_name = entry.file_name().to_string_lossy().to_string();
-        let file_path = entry.path();
+   for (file_name, file_path) in files {
         
         // Get file metadata (fail silently if not accessible)
         let metadata = fs::metadata(&file_path).ok();
@@ -100,11 +206,19 @@ fn scan_folder(cached_folder: &mut CachedFolder, folder_path: &Path) {
         match cached_folder.files.entry(file_name.clone()) {
             Entry::Vacant(entry) => {
                 // New file not in cache - mark as NotInCache
+                // Parse it from the start: creation time is unreliable for copied/moved transcripts
                 entry.insert(CachedFile {
                     file_name,
-                    cache_time: created_time,
+                    cache_time: DateTime::<Utc>::UNIX_EPOCH,
                     blocks: HashMap::new(),
                     per_hour: HashMap::new(),
+                    session_id: None,
+                    missing_since: None,
+                    archived_until: None,
+                    claude_version: None,
+                    parse_errors: Vec::new(),
+                    recent_events: Vec::new(),
+                    open_tool_calls: HashMap::new(),
                     cache_status: CacheStatus::NotInCache,
                     modified_time,
                     created_time,
@@ -127,18 +241,45 @@ fn scan_folder(cached_folder: &mut CachedFolder, folder_path: &Path) {
    }
 }
 
+/// Collect `*.jsonl` files under `dir`, keyed by their path relative to the project folder
+/// Symlinks are followed; a directory already visited (by canonical path) is skipped,
+/// which breaks symlink cycles
+fn collect_transcripts(dir: &Path, prefix: &str, visited: &mut HashSet<PathBuf>, files: &mut Vec<(String, PathBuf)>) {
+    let entries = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok());
+
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let key = match prefix.is_empty() {
+            true => name,
+            false => format!("{}/{}", prefix, name),
+        };
+
+        if path.is_dir() {
+            if fs::canonicalize(&path).is_ok_and(|canonical| visited.insert(canonical)) {
+                collect_transcripts(&path, &key, visited, files);
+            }
+        } else if path.is_file() && path.extension().is_some_and(|ext| ext == "jsonl") {
+            files.push((key, path));
+        }
+    }
+}
+
 /// Update cache with current folder structure from base_path
 /// Creates new entries or updates existing ones
 /// base_path should be the Claude data directory (e.g. ~/.claude)
 /// This function automatically scans the /projects subdirectory
-pub fn refresh_cache(cache: &mut CacheInfo, base_path: &str) {
+pub fn refresh_cache(cache: &mut CachedRoot, base_path: &str, index: &mut MessageIndex) {
     let projects_path = Path::new(base_path).join("projects");
     
     for (folder_name, folder) in cache.folders.iter_mut() {
         for (file_name, file) in folder.files.iter_mut() {
             if matches!(file.cache_status, CacheStatus::NeedsRefresh | CacheStatus::NotInCache) {
                 let file_path: PathBuf = projects_path.join(folder_name).join(file_name);
-                refresh_single_file(file, &file_path);
+                refresh_single_file(file, &file_path, index);
             }
         }
     }
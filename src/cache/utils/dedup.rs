@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    cache::{CacheInfo, CachedFile},
+    claude_types::usage_line::UsageLine,
+};
+
+/// API responses already counted somewhere in the cache
+/// Resumed/forked sessions copy earlier messages into a new transcript, and streamed
+/// responses log the same message once per content block: only the first one counts
+#[derive(Debug, Default)]
+pub struct MessageIndex {
+    seen: HashSet<u64>,
+}
+
+impl MessageIndex {
+    /// Index every message counted in the cache (all roots)
+    pub fn build(cache: &CacheInfo) -> Self {
+        let seen = cache.roots.values()
+            .flat_map(|root| root.folders.values())
+            .flat_map(|folder| folder.files.values())
+            .flat_map(|file| file.per_hour.values())
+            .flat_map(|hour| hour.message_ids.iter().copied())
+            .collect();
+        Self { seen }
+    }
+
+    /// Forget messages of the hours about to be re-parsed, so the file counts them again
+    pub fn release(&mut self, file: &CachedFile, boundary: DateTime<Utc>) {
+        for hour in file.per_hour.values().filter(|hour| hour.hour_start >= boundary) {
+            for id in &hour.message_ids {
+                self.seen.remove(id);
+            }
+        }
+    }
+
+    /// Claim a message, false if it was already counted
+    pub fn claim(&mut self, id: u64) -> bool {
+        self.seen.insert(id)
+    }
+}
+
+/// Key of the API response behind a transcript entry: (message.id, requestId)
+/// None for entries without a message id (user messages, summaries...)
+pub fn message_key(line: &UsageLine) -> Option<u64> {
+    let message_id = line.message.as_ref()?.id.as_deref()?;
+    let request_id = line.request_id.as_deref().unwrap_or("");
+    Some(fnv1a(&[message_id.as_bytes(), b":", request_id.as_bytes()]))
+}
+
+/// Key of a tool call (tool_use ids are unique, copies in forked sessions keep them)
+pub fn tool_key(tool_use_id: &str) -> u64 {
+    fnv1a(&[b"tool:", tool_use_id.as_bytes()])
+}
+
+/// 64-bit FNV-1a, stable across runs and platforms (unlike the std hasher)
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
@@ -0,0 +1,70 @@
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    io::{self, Write},
+    path::Path,
+    process,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How long a writer waits for another process to release the lock
+pub const LOCK_TIMEOUT: Duration = Duration::from_millis(250);
+/// Poll interval while waiting for the lock
+const LOCK_RETRY: Duration = Duration::from_millis(10);
+
+/// Advisory lock held on a lock file (`flock`).
+/// The lock is released when dropped, or by the OS when the process dies, so a
+/// crashed process never leaves it behind. The file itself stays in place: removing
+/// it would let a waiter lock a file that is no longer the lock file.
+pub struct FileLock {
+    _file: File,
+}
+
+impl FileLock {
+    /// Try to take the lock, waiting at most `timeout`
+    /// Returns None if another process still holds it
+    pub fn acquire(path: &Path, timeout: Duration) -> Option<Self> {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let mut file = OpenOptions::new().write(true).create(true).truncate(false).open(path).ok()?;
+
+        let start = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => {
+                    let _ = file.set_len(0).and_then(|_| write!(file, "{}", process::id()));
+                    return Some(Self { _file: file });
+                }
+                Err(TryLockError::WouldBlock) => {}
+                Err(TryLockError::Error(_)) => return None,
+            }
+
+            if start.elapsed() >= timeout {
+                return None;
+            }
+            thread::sleep(LOCK_RETRY);
+        }
+    }
+}
+
+/// Write `content` to a temporary file next to `path`, then rename it over `path`
+/// Readers never see a partially written file
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, process::id()));
+
+    let result = fs::write(&tmp_path, content).and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Last modification time of a file, None if it doesn't exist
+pub fn file_stamp(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -1,5 +1,13 @@
+mod binary;
 mod cache;
+mod dedup;
+mod lock;
 mod parse;
+mod reset_time;
 
 pub use parse::*;
-pub use cache::*;
\ No newline at end of file
+pub use binary::*;
+pub use cache::*;
+pub use dedup::*;
+pub use lock::*;
+pub use reset_time::*;
\ No newline at end of file
@@ -1,34 +1,74 @@
 use std::{collections::HashMap, fs, path::PathBuf};
-use chrono::{DateTime, Utc, Timelike};
+use chrono::{DateTime, Duration, Utc};
 
 use crate::{
-    cache::{BlockLine, CachedFile, PerHourBlock},
-    claude_types::transcript_entry::ClaudeEntry, common::duration::round_to_hour_boundary,
+    cache::{message_key, extract_reset_time_text, tool_key, BlockLine, CachedFile, LimitKind, MessageIndex, ModelUsage, ParseHealth, PerHourBlock, ToolCall, UsageEvent, RECENT_EVENT_HOURS},
+    claude_types::{
+        content::ContentBlock, message::MessageContent, tool_use::ToolUseBlock,
+        usage_line::UsageLine,
+    },
+    common::duration::round_to_hour_boundary,
 };
 
 /// Refresh a single file by parsing JSONL content and populating cache data
-pub fn refresh_single_file(file: &mut CachedFile, file_path: &PathBuf) {
+/// API responses already counted elsewhere in `index` are skipped
+pub fn refresh_single_file(file: &mut CachedFile, file_path: &PathBuf, index: &mut MessageIndex) {
     // Parse entries since the hour boundary (not just cache_time) to get complete hours
     let boundary = round_to_hour_boundary(file.cache_time);
-    let new_entries = parse_file_since_boundary(
+    let parsed = parse_transcript_since(
         file_path.to_string_lossy().as_ref(), 
         boundary
     );
+    let new_entries = parsed.entries;
     
-    if new_entries.is_empty() {
+    if new_entries.is_empty() && parsed.health.is_empty() {
         // Mark as Fresh even if no new entries (file was checked)
         file.cache_status = crate::cache::CacheStatus::Fresh;
         return;
     }
     
+    // Remember the session id so the transcript can be followed if it gets moved
+    if file.session_id.is_none() {
+        file.session_id = new_entries.iter().find_map(|entry| entry.session_id.clone());
+    }
+    
     // Process entries into per-hour blocks and limit events
-    let new_per_hour_blocks = generate_per_hour_blocks(&new_entries);
+    index.release(file, boundary);
+    let mut new_events = Vec::new();
+    let mut tool_errors = Vec::new();
+    let mut new_per_hour_blocks = generate_per_hour_blocks(&new_entries, index, &mut new_events, &mut file.open_tool_calls, &mut tool_errors);
     let new_block_lines = generate_block_lines(&new_entries);
     
+    // Parse health, including hours where no line could be parsed
+    for (hour_start, health) in parsed.health {
+        new_per_hour_blocks.entry(hour_start)
+            .or_insert_with(|| PerHourBlock::new(hour_start, hour_start))
+            .parse_health = health;
+    }
+    if parsed.version.is_some() {
+        file.claude_version = parsed.version;
+    }
+    if !parsed.errors.is_empty() {
+        let older = std::mem::take(&mut file.parse_errors);
+        file.parse_errors = parsed.errors.into_iter().chain(older).take(MAX_PARSE_ERRORS).collect();
+    }
+    
     // Merge per-hour blocks (replace existing hours with new data)
     for (hour_start, new_block) in new_per_hour_blocks {
         file.per_hour.insert(hour_start, new_block); // Replace if exists
     }
+
+    // Errors go to the hour of their call, which may be older than the hours parsed again
+    for (hour_start, tool) in tool_errors {
+        if let Some(hour) = file.per_hour.get_mut(&hour_start) {
+            hour.tools.entry(tool).or_default().errors += 1;
+        }
+    }
+    
+    // Recent events since the boundary were parsed again, older ones expire
+    let horizon = Utc::now() - Duration::hours(RECENT_EVENT_HOURS);
+    file.recent_events.retain(|event| event.timestamp <= boundary && event.timestamp >= horizon);
+    file.recent_events.extend(new_events);
     
     // Merge/replace block lines by timestamp to avoid duplicates
     for (ts, block) in new_block_lines {
@@ -40,77 +80,121 @@ pub fn refresh_single_file(file: &mut CachedFile, file_path: &PathBuf) {
     file.cache_status = crate::cache::CacheStatus::Fresh;
 }
 
-/// Parse entries strictly newer than `boundary`.
-/// - If the file doesn't exist → returns an empty Vec (silent).
-/// - Otherwise: reverse-parse and stop when `timestamp <= boundary`,
-///   then restore chronological order.
-pub fn parse_file_since_boundary(
+/// Parse errors kept per file
+pub const MAX_PARSE_ERRORS: usize = 5;
+
+/// Result of parsing the end of a transcript
+#[derive(Debug, Default)]
+pub struct ParsedTranscript {
+    /// Entries in chronological order
+    pub entries: Vec<UsageLine>,
+    /// Line counters per hour (hour_start -> health)
+    pub health: HashMap<DateTime<Utc>, ParseHealth>,
+    /// Why lines carrying usage could not be parsed, newest first
+    pub errors: Vec<String>,
+    /// Claude Code version of the newest entry
+    pub version: Option<String>,
+}
+
+/// Parse entries strictly newer than `boundary`, reading the file backwards and
+/// stopping at the first entry at or before it (a missing file parses as empty).
+/// Only the fields needed for accounting are read (see `UsageLine`), and lines that
+/// can't be read are counted per hour
+/// Lines without timestamp are counted in the hour of the line following them
+pub fn parse_transcript_since(
     file_path: &str,
     boundary: DateTime<Utc>,
-) -> Vec<ClaudeEntry> {
+) -> ParsedTranscript {
     let content = match fs::read_to_string(file_path) {
         Ok(s) => s,
-        Err(_)  => return Vec::new(),
+        Err(_)  => return ParsedTranscript::default(),
     };
 
-    let mut entries: Vec<_> = content
-        .lines()
-        .rev()
-        .filter_map(|line| parse_line(line))
-        .take_while(|entry| 
-            {
-                let timestamp_str = entry.timestamp();
-                let timestamp = DateTime::parse_from_rfc3339(timestamp_str.unwrap_or(""))
-                    .unwrap_or_else(|_| DateTime::from(Utc::now()));
-                timestamp > boundary
-            })
-        .collect();
-
-    entries.reverse();
-    entries
-}
+    let mut parsed = ParsedTranscript::default();
+    let mut hour = round_to_hour_boundary(Utc::now());
+
+    for line in content.lines().rev().map(str::trim).filter(|line| !line.is_empty()) {
+        let entry = serde_json::from_str::<UsageLine>(line);
 
-/// Parse single JSONL line into ClaudeBarUsageEntry
-pub fn parse_line(line: &str) -> Option<ClaudeEntry> {
-    let line = line.trim();
-    if line.is_empty() {
-        return None;
+        if let Some(timestamp) = entry.as_ref().ok().and_then(|entry| entry.timestamp_utc()) {
+            if timestamp <= boundary {
+                break;
+            }
+            hour = round_to_hour_boundary(timestamp);
+        }
+        let health = parsed.health.entry(hour).or_default();
+
+        match entry {
+            Ok(entry) if entry.is_known() => {
+                health.parsed_lines += 1;
+                if entry.usage().is_some() {
+                    health.usage_lines += 1;
+                }
+                if parsed.version.is_none() {
+                    parsed.version = entry.version.clone().filter(|version| !version.is_empty());
+                }
+                parsed.entries.push(entry);
+            }
+            // Other entry types (snapshots, queue operations...) are expected
+            Ok(_) => health.unknown_lines += 1,
+            Err(error) => {
+                // Valid JSON of an unexpected shape, or not JSON at all
+                match serde_json::from_str::<serde_json::Value>(line) {
+                    Ok(value) => {
+                        health.unknown_lines += 1;
+                        if value.pointer("/message/usage").is_some() {
+                            health.lost_usage_lines += 1;
+                            let version = value.get("version").and_then(|v| v.as_str()).unwrap_or("?");
+                            parsed.errors.push(format!("v{}: {}", version, error));
+                        }
+                    }
+                    Err(_) => {
+                        health.failed_lines += 1;
+                        if line.contains("\"usage\"") {
+                            health.lost_usage_lines += 1;
+                        }
+                        parsed.errors.push(format!("invalid JSON: {}", error));
+                    }
+                }
+            }
+        }
     }
 
-    serde_json::from_str::<ClaudeEntry>(line).ok()
+    parsed.errors.truncate(MAX_PARSE_ERRORS);
+    parsed.entries.reverse();
+    parsed
 }
 
-/// Generate per-hour usage blocks from ClaudeEntry list  
-fn generate_per_hour_blocks(entries: &[ClaudeEntry]) -> HashMap<DateTime<Utc>, PerHourBlock> {
+/// Generate per-hour usage blocks from transcript lines
+/// Messages of the last `RECENT_EVENT_HOURS` hours are also added to `events`
+/// Tool results are attributed through `tool_calls`, which keeps the calls whose
+/// result the next refresh may parse; their errors are added to `tool_errors` as
+/// (hour of the call, tool), so that a call and its error are always counted together
+fn generate_per_hour_blocks(
+    entries: &[UsageLine],
+    index: &mut MessageIndex,
+    events: &mut Vec<UsageEvent>,
+    tool_calls: &mut HashMap<String, ToolCall>,
+    tool_errors: &mut Vec<(DateTime<Utc>, String)>,
+) -> HashMap<DateTime<Utc>, PerHourBlock> {
     let mut hour_blocks: HashMap<DateTime<Utc>, PerHourBlock> = HashMap::new();
+    let horizon = Utc::now() - Duration::hours(RECENT_EVENT_HOURS);
+    // Results counted in this pass (tool_use id -> time of the result)
+    let mut results: HashMap<String, DateTime<Utc>> = HashMap::new();
     
     for entry in entries {
-        if let ClaudeEntry::Transcript(transcript) = entry {
+        if let Some(message) = &entry.message {
             // Parse timestamp
-            let timestamp_dt = match DateTime::parse_from_rfc3339(&transcript.timestamp) {
-                Ok(dt) => dt.with_timezone(&Utc),
-                Err(_) => continue,
+            let Some(timestamp_dt) = entry.timestamp_utc() else {
+                continue;
             };
             
             // Round down to hour boundary (e.g., 14:32:15 -> 14:00:00)
             let hour_start = round_to_hour_boundary(timestamp_dt);
-            let hour_end = hour_start + chrono::Duration::hours(1) - chrono::Duration::seconds(1);
             
             // Get or create hour block
-            let hour_block = hour_blocks.entry(hour_start).or_insert_with(|| PerHourBlock {
-                hour_start,
-                hour_end,
-                min_timestamp: timestamp_dt,
-                max_timestamp: timestamp_dt,
-                input_tokens: 0,
-                output_tokens: 0,
-                cache_creation_tokens: 0,
-                cache_read_tokens: 0,
-                assistant_messages: 0,
-                user_messages: 0,
-                total_content_length: 0,
-                entry_count: 0,
-            });
+            let hour_block = hour_blocks.entry(hour_start)
+                .or_insert_with(|| PerHourBlock::new(hour_start, timestamp_dt));
             
             // Update min/max timestamps
             if timestamp_dt < hour_block.min_timestamp {
@@ -120,135 +204,188 @@ fn generate_per_hour_blocks(entries: &[ClaudeEntry]) -> HashMap<DateTime<Utc>, P
                 hour_block.max_timestamp = timestamp_dt;
             }
             
+            hour_block.entry_count += 1;
+            
+            // Streamed or copied API response already counted: only its content is new
+            let message_id = message_key(entry);
+            let is_duplicate = message_id.is_some_and(|id| !index.claim(id));
+            if is_duplicate {
+                hour_block.duplicate_entries += 1;
+            } else if let Some(id) = message_id {
+                hour_block.message_ids.push(id);
+            }
+            
             // Count message types and content length
-            if let Some(role) = &transcript.message.role {
+            if let Some(role) = message.role.as_ref().filter(|_| !is_duplicate) {
                 match role.as_str() {
                     "assistant" => hour_block.assistant_messages += 1,
                     "user" => hour_block.user_messages += 1,
                     _ => {}
                 }
+                if timestamp_dt >= horizon && matches!(role.as_str(), "assistant" | "user") {
+                    let usage = message.usage.as_ref().filter(|_| role == "assistant");
+                    events.push(UsageEvent {
+                        timestamp: timestamp_dt,
+                        output_tokens: usage.map_or(0, |usage| usage.output_tokens),
+                        model: message.model.clone().filter(|_| role == "assistant"),
+                    });
+                }
             }
             
             // Add content length (from content field)
-            match &transcript.message.content {
-                crate::claude_types::message::MessageContent::String(text) => {
+            match &message.content {
+                MessageContent::String(text) => {
                     hour_block.total_content_length += text.len() as u64;
                 }
-                crate::claude_types::message::MessageContent::Blocks(content_items) => {
+                MessageContent::Blocks(content_items) => {
                     for content_item in content_items {
                         if let Some(text) = content_item.extract_text() {
                             hour_block.total_content_length += text.len() as u64;
                         }
                     }
                 }
+                MessageContent::Raw(_) => {}
+            }
+            
+            // Tool calls (once per tool_use id) and the errors of their results
+            if let MessageContent::Blocks(content_items) = &message.content {
+                for content_item in content_items {
+                    match content_item {
+                        ContentBlock::ToolUse(tool_use) => {
+                            let key = tool_key(&tool_use.id);
+                            if index.claim(key) {
+                                hour_block.message_ids.push(key);
+                                record_tool_use(hour_block, tool_use);
+                                tool_calls.insert(tool_use.id.clone(), ToolCall {
+                                    name: tool_use.name.clone(),
+                                    timestamp: timestamp_dt,
+                                    error_counted: false,
+                                });
+                            }
+                        }
+                        ContentBlock::ToolResult { tool_use_id, is_error, .. } => {
+                            let Some(call) = tool_calls.get_mut(tool_use_id) else {
+                                continue;
+                            };
+                            results.entry(tool_use_id.clone()).or_insert(timestamp_dt);
+                            if *is_error && !call.error_counted {
+                                call.error_counted = true;
+                                tool_errors.push((round_to_hour_boundary(call.timestamp), call.name.clone()));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
             }
             
             // Add token usage if available
-            if let Some(usage) = &transcript.message.usage {
+            if let Some(usage) = message.usage.as_ref().filter(|_| !is_duplicate) {
                 hour_block.input_tokens += usage.input_tokens;
                 hour_block.output_tokens += usage.output_tokens;
+                hour_block.add_slot_output(timestamp_dt, usage.output_tokens);
                 hour_block.cache_creation_tokens += usage.cache_creation_input_tokens;
                 hour_block.cache_read_tokens += usage.cache_read_input_tokens;
+
+                let model = message.model.as_deref().unwrap_or("unknown");
+                let entry_usage = ModelUsage {
+                    input_tokens: usage.input_tokens,
+                    output_tokens: usage.output_tokens,
+                    cache_creation_tokens: usage.cache_creation_input_tokens,
+                    cache_read_tokens: usage.cache_read_input_tokens,
+                    messages: 1,
+                };
+                hour_block.models.entry(model.to_string()).or_default().merge(&entry_usage);
+                if entry.is_sidechain {
+                    hour_block.sidechain.merge(&entry_usage);
+                }
+
+                // Server-side tools are billed per request, on top of tokens
+                if let Some(server_tool_use) = &usage.server_tool_use {
+                    hour_block.web_search_requests += server_tool_use.web_search_requests;
+                }
+                if let Some(tier) = &usage.service_tier {
+                    *hour_block.service_tiers.entry(tier.clone()).or_default() += 1;
+                }
             }
-            
-            hour_block.entry_count += 1;
         }
     }
+
+    // The next refresh parses again from the current hour: calls answered before it
+    // are done, the others may still get (or count again) their result
+    let next_boundary = round_to_hour_boundary(Utc::now());
+    tool_calls.retain(|id, call| {
+        call.timestamp >= horizon && results.get(id).is_none_or(|answered| *answered >= next_boundary)
+    });
     
     hour_blocks
 }
 
-/// Generate block/limit events from ClaudeEntry list
-fn generate_block_lines(entries: &[ClaudeEntry]) -> HashMap<DateTime<Utc>, BlockLine> {
-    let mut block_lines: HashMap<DateTime<Utc>, BlockLine> = HashMap::new();
-    
-    for entry in entries {
-        match entry {
-            // Look for limit-related messages in Transcript entries
-            ClaudeEntry::Transcript(transcript) => {
-                // Parse timestamp
-                let block_timestamp_utc = match DateTime::parse_from_rfc3339(&transcript.timestamp) {
-                    Ok(dt) => dt.with_timezone(&Utc),
-                    Err(_) => continue,
-                };
-                
-                // Check if this is an API error message (indicates limit/rate limit failure)
-                if transcript.is_api_error_message {
-                    // Extract full message text
-                    let full_text = match &transcript.message.content {
-                        crate::claude_types::message::MessageContent::String(text) => text.clone(),
-                        crate::claude_types::message::MessageContent::Blocks(content_items) => {
-                            content_items.iter()
-                                .filter_map(|item| item.extract_text())
-                                .collect::<Vec<_>>()
-                                .join(" ")
-                        }
-                    };
-                    
-                    let reset_text = extract_reset_time_text(&full_text);
-                    let unlock_timestamp = calculate_unlock_time(block_timestamp_utc, &reset_text);
-                    
-                    block_lines.insert(
-                        block_timestamp_utc,
-                        BlockLine {
-                            unlock_timestamp,
-                            reset_text,
-                        }
-                    );
-                }
-            },
-            // Summary and Unknown entries are kept for debug parsing stats but don't contain limits
-            _ => {} // Skip Summary/Unknown entries - they don't contain limit events
-        }
+/// Count a tool call, with its Bash program or edited file
+fn record_tool_use(hour_block: &mut PerHourBlock, tool_use: &ToolUseBlock) {
+    hour_block.tools.entry(tool_use.name.clone()).or_default().calls += 1;
+
+    let counter = match tool_use.name.as_str() {
+        "Bash" => tool_use.get_param_str("command")
+            .and_then(|command| bash_program(&command))
+            .map(|program| hour_block.bash_commands.entry(program)),
+        "Edit" | "MultiEdit" | "Write" => tool_use.get_param_str("file_path")
+            .map(|path| hour_block.edited_files.entry(path)),
+        "NotebookEdit" => tool_use.get_param_str("notebook_path")
+            .map(|path| hour_block.edited_files.entry(path)),
+        _ => None,
+    };
+    if let Some(counter) = counter {
+        *counter.or_default() += 1;
     }
-    block_lines
 }
 
-/// Extract reset time from limit message (e.g., "5-hour limit reached ∙ resets 5pm" -> "5pm")
-fn extract_reset_time_text(message: &str) -> String {
-    // Look for the "resets " pattern and capture what comes after
-    if let Some(pos) = message.find("resets ") {
-        let after_resets = &message[pos + 7..]; // Skip "resets "
-        
-        // Take the first word/time after "resets " (until space or end of string)
-        let reset_time = after_resets.split_whitespace().next()
-            .unwrap_or("unknown")
-            .to_string();
-        
-        return reset_time;
+/// Tools whose first argument is a subcommand worth keeping ("git commit", "cargo test")
+const BASH_SUBCOMMAND_TOOLS: &[&str] = &["git", "cargo", "npm", "pnpm", "yarn", "docker", "kubectl", "gh", "go", "uv"];
+
+/// Program run by a shell command, skipping leading `VAR=value` assignments
+/// e.g. `RUST_LOG=debug cargo test -q && ls` -> "cargo test"
+fn bash_program(command: &str) -> Option<String> {
+    let mut words = command.split_whitespace().skip_while(|word| word.contains('='));
+    let program = words.next()?;
+    let program = program.rsplit('/').next().unwrap_or(program);
+
+    match words.next() {
+        Some(sub) if BASH_SUBCOMMAND_TOOLS.contains(&program) && !sub.starts_with('-') => {
+            Some(format!("{} {}", program, sub))
+        }
+        _ => Some(program.to_string()),
     }
-    
-    // Fallback: if no "resets " found, return unknown
-    "unknown".to_string()
 }
 
-/// Calculate unlock timestamp from block time and reset text
-/// e.g., blocked at "08-22 16:43" with reset "5pm" -> unlock at "08-22 17:00"
-fn calculate_unlock_time(block_time: DateTime<Utc>, reset_text: &str) -> Option<DateTime<Utc>> {
-    if reset_text == "unknown" {
-        return None;
-    }
-    
-    // Parse different time formats
-    let target_hour = match reset_text.to_lowercase().as_str() {
-        "12am" => 0,
-        "1am" => 1, "2am" => 2, "3am" => 3, "4am" => 4, "5am" => 5,
-        "6am" => 6, "7am" => 7, "8am" => 8, "9am" => 9, "10am" => 10, "11am" => 11,
-        "12pm" => 12,
-        "1pm" => 13, "2pm" => 14, "3pm" => 15, "4pm" => 16, "5pm" => 17,
-        "6pm" => 18, "7pm" => 19, "8pm" => 20, "9pm" => 21, "10pm" => 22, "11pm" => 23,
-        _ => return None, // Unknown format
-    };
-    
-    // Start with the same date as the block
-    let mut unlock_date = block_time.date_naive();
+/// Generate block/limit events from transcript lines
+fn generate_block_lines(entries: &[UsageLine]) -> HashMap<DateTime<Utc>, BlockLine> {
+    let mut block_lines: HashMap<DateTime<Utc>, BlockLine> = HashMap::new();
     
-    // If the target hour has already passed today, move to tomorrow
-    if target_hour <= block_time.hour() as u8 {
-        unlock_date = unlock_date.succ_opt()?;
+    // API error messages indicate a limit/rate limit failure
+    for entry in entries.iter().filter(|entry| entry.is_api_error_message) {
+        let (Some(message), Some(block_timestamp_utc)) = (&entry.message, entry.timestamp_utc()) else {
+            continue;
+        };
+
+        // Extract full message text
+        let full_text = match &message.content {
+            MessageContent::String(text) => text.clone(),
+            MessageContent::Blocks(content_items) => {
+                content_items.iter()
+                    .filter_map(|item| item.extract_text())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+            MessageContent::Raw(_) => String::new(),
+        };
+        
+        block_lines.insert(
+            block_timestamp_utc,
+            BlockLine {
+                reset_text: extract_reset_time_text(&full_text),
+                kind: LimitKind::from_message(&full_text),
+            }
+        );
     }
-    
-    // Create the unlock timestamp
-    let unlock_time = unlock_date.and_hms_opt(target_hour as u32, 0, 0)?;
-    Some(DateTime::from_naive_utc_and_offset(unlock_time, Utc))
+    block_lines
 }
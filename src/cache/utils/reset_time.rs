@@ -0,0 +1,213 @@
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use regex::Regex;
+
+/// Separators Claude puts between the reset phrase and the rest of a limit message
+const PHRASE_TERMINATORS: &[char] = &['\n', '∙', '·', '|'];
+
+// Patterns are compiled once: they run for every limit line of a cache build
+static RESETS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\bresets\s+(?:at\s+)?").unwrap());
+static LEGACY_EPOCH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"limit reached\|(\d{9,11})").unwrap());
+static RELATIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^in\s+(?:(\d+)\s*(?:hours|hour|hrs|hr|h)\s*(?:and\s+)?)?(?:(\d+)\s*(?:minutes|minute|mins|min|m)\b)?").unwrap()
+});
+static ZONE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\(([^)]+)\)").unwrap());
+static DATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:on\s+)?([a-z]{3})[a-z]*\.?\s+(\d{1,2})(?:st|nd|rd|th)?(?:,?\s+(\d{4}))?,?\s*").unwrap()
+});
+static WEEKDAY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:on\s+)?(mon|tue|wed|thu|fri|sat|sun)[a-z]*\.?,?\s*").unwrap());
+static CLOCK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:at\s+)?(\d{1,2})(?::(\d{2}))?\s*(am|pm|a\.m\.|p\.m\.)?").unwrap());
+
+/// Extract the reset phrase from a limit message, or "unknown" when there is none
+/// e.g. "5-hour limit reached ∙ resets 5:30pm (Europe/Paris)" -> "5:30pm (Europe/Paris)"
+pub fn extract_reset_time_text(message: &str) -> String {
+    if let Some(epoch) = legacy_epoch(message) {
+        return epoch.to_string();
+    }
+
+    reset_phrase(message)
+        .map(str::to_string)
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Resolve a limit message to the UTC time the limit lifts
+///
+/// Handles the formats Claude has used over time:
+/// - `Claude AI usage limit reached|1755882000` (unix seconds)
+/// - `resets 5pm`, `resets 5:30pm`, `resets 17:00`
+/// - `resets in 2h30m`, `resets in 45 minutes`
+/// - `resets Oct 20, 9am`, `resets Oct 20, 2025 at 9:15am`
+/// - `resets Mon 9am` (next Monday, weekly limits)
+///
+/// Any of the absolute forms may carry an IANA zone, e.g. `resets 5pm (Europe/Paris)`;
+/// without one the time is read as UTC. Times without a date resolve to the next
+/// occurrence after `block_time`, likewise for weekdays.
+pub fn parse_reset_time(message: &str, block_time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    parse_reset_time_in(message, block_time, Tz::UTC)
+}
+
+/// `parse_reset_time` reading times without a zone in `default_zone`
+/// (Claude prints them in the user's local time)
+pub fn parse_reset_time_in(message: &str, block_time: DateTime<Utc>, default_zone: Tz) -> Option<DateTime<Utc>> {
+    if let Some(epoch) = legacy_epoch(message) {
+        return DateTime::from_timestamp(epoch, 0);
+    }
+
+    let phrase = reset_phrase(message)?;
+    let lower = phrase.to_lowercase();
+
+    if let Some(duration) = parse_relative(&lower) {
+        return Some(block_time + duration);
+    }
+
+    let tz = parse_timezone(phrase, default_zone)?;
+    let local_now = block_time.with_timezone(&tz).naive_local();
+
+    let (date, weekday, rest) = if let Some((date, consumed)) = parse_date(&lower, local_now) {
+        (Some(date), None, &lower[consumed..])
+    } else if let Some((weekday, consumed)) = parse_weekday(&lower) {
+        (None, Some(weekday), &lower[consumed..])
+    } else {
+        (None, None, lower.as_str())
+    };
+    let time = match parse_clock(rest) {
+        Some(time) => time,
+        None if date.is_some() || weekday.is_some() => NaiveTime::MIN,
+        None => return None,
+    };
+
+    let target = match (date, weekday) {
+        (Some(date), _) => {
+            let target = date.and_time(time);
+            // A date months in the past ("Jan 2" seen in late December) is next year's
+            if target < local_now - Duration::days(180) {
+                with_year(date, date.year() + 1)?.and_time(time)
+            } else {
+                target
+            }
+        }
+        (None, Some(weekday)) => {
+            let days_ahead = (7 + weekday.num_days_from_monday() - local_now.weekday().num_days_from_monday()) % 7;
+            let target = (local_now.date() + Duration::days(days_ahead as i64)).and_time(time);
+            if target <= local_now {
+                target + Duration::days(7)
+            } else {
+                target
+            }
+        }
+        (None, None) => {
+            let today = local_now.date().and_time(time);
+            if today <= local_now {
+                today + Duration::days(1)
+            } else {
+                today
+            }
+        }
+    };
+
+    resolve_local(&tz, target)
+}
+
+/// Resolve a phrase of `extract_reset_time_text` (e.g. "5pm", "in 2h", "1755882000"),
+/// like `parse_reset_time_in` does for the whole message
+pub fn resolve_reset_text(reset_text: &str, block_time: DateTime<Utc>, default_zone: Tz) -> Option<DateTime<Utc>> {
+    if (9..=11).contains(&reset_text.len()) && reset_text.chars().all(|c| c.is_ascii_digit()) {
+        return DateTime::from_timestamp(reset_text.parse().ok()?, 0);
+    }
+    parse_reset_time_in(&format!("resets {}", reset_text), block_time, default_zone)
+}
+
+/// Text following "resets" up to the next separator
+fn reset_phrase(message: &str) -> Option<&str> {
+    let after = &message[RESETS.find(message)?.end()..];
+
+    let end = after.find(PHRASE_TERMINATORS).unwrap_or(after.len());
+    let phrase = after[..end].trim().trim_end_matches('.').trim();
+    (!phrase.is_empty()).then_some(phrase)
+}
+
+/// Older clients reported the reset as `...limit reached|<unix seconds>`
+fn legacy_epoch(message: &str) -> Option<i64> {
+    LEGACY_EPOCH.captures(message)?.get(1)?.as_str().parse().ok()
+}
+
+/// "in 2h30m", "in 2 hours 30 minutes", "in 45 min"
+fn parse_relative(phrase: &str) -> Option<Duration> {
+    let caps = RELATIVE.captures(phrase)?;
+    let hours = caps.get(1).and_then(|m| m.as_str().parse::<i64>().ok());
+    let minutes = caps.get(2).and_then(|m| m.as_str().parse::<i64>().ok());
+    if hours.is_none() && minutes.is_none() {
+        return None;
+    }
+    Some(Duration::hours(hours.unwrap_or(0)) + Duration::minutes(minutes.unwrap_or(0)))
+}
+
+/// Zone named in parentheses, `default` when absent, None when present but unknown
+fn parse_timezone(phrase: &str, default: Tz) -> Option<Tz> {
+    match ZONE.captures(phrase) {
+        Some(caps) => {
+            let name = caps.get(1)?.as_str().trim();
+            name.parse::<Tz>().ok()
+        }
+        None => Some(default),
+    }
+}
+
+/// Leading "Oct 20" / "October 20, 2025" / "on Oct 20th"; returns the date and bytes consumed
+fn parse_date(phrase: &str, local_now: NaiveDateTime) -> Option<(NaiveDate, usize)> {
+    let caps = DATE.captures(phrase)?;
+    let month = month_number(caps.get(1)?.as_str())?;
+    let day = caps.get(2)?.as_str().parse().ok()?;
+    let year = match caps.get(3) {
+        Some(year) => year.as_str().parse().ok()?,
+        None => local_now.year(),
+    };
+    let date = NaiveDate::from_ymd_opt(year, month, day)?;
+    Some((date, caps.get(0)?.end()))
+}
+
+/// Leading "Mon" / "Monday" / "on Mon,"; returns the weekday and bytes consumed
+fn parse_weekday(phrase: &str) -> Option<(Weekday, usize)> {
+    let caps = WEEKDAY.captures(phrase)?;
+    let weekday = caps.get(1)?.as_str().parse().ok()?;
+    Some((weekday, caps.get(0)?.end()))
+}
+
+/// "9am", "5:30 pm", "at 17:00"; bare numbers are rejected as ambiguous
+fn parse_clock(phrase: &str) -> Option<NaiveTime> {
+    let caps = CLOCK.captures(phrase)?;
+    let hour: u32 = caps.get(1)?.as_str().parse().ok()?;
+    let minute: Option<u32> = caps.get(2).and_then(|m| m.as_str().parse().ok());
+    let meridiem = caps.get(3).map(|m| m.as_str().starts_with('p'));
+
+    let hour = match meridiem {
+        Some(pm) => {
+            if !(1..=12).contains(&hour) {
+                return None;
+            }
+            (hour % 12) + if pm { 12 } else { 0 }
+        }
+        None if minute.is_some() => hour,
+        None => return None,
+    };
+    NaiveTime::from_hms_opt(hour, minute.unwrap_or(0), 0)
+}
+
+fn month_number(abbrev: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    MONTHS.iter().position(|m| *m == abbrev).map(|i| i as u32 + 1)
+}
+
+fn with_year(date: NaiveDate, year: i32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, date.month(), date.day())
+}
+
+/// Local wall time to UTC; a time skipped by a DST jump resolves to just after the jump
+fn resolve_local(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(local + Duration::hours(1))).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+}
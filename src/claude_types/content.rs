@@ -1,4 +1,6 @@
+use std::borrow::Cow;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use super::tool_use::ToolUseBlock;
 
 /// A content block within a message - can be text, tool_use, tool_result, etc.
@@ -18,7 +20,8 @@ pub enum ContentBlock {
     ToolResult {
         #[serde(rename = "tool_use_id")]
         tool_use_id: String,
-        content: String,
+        #[serde(default)]
+        content: ToolResultContent,
         #[serde(default)]
         is_error: bool,
     },
@@ -30,18 +33,82 @@ pub enum ContentBlock {
         signature: Option<String>,
     },
     
+    /// Thinking block encrypted by the API
+    #[serde(rename = "redacted_thinking")]
+    RedactedThinking {
+        #[serde(default)]
+        data: String,
+    },
+    
+    /// Image (screenshots, pasted images, Read tool on an image)
+    #[serde(rename = "image")]
+    Image {
+        #[serde(default)]
+        source: Value,
+    },
+    
+    /// Document (PDF...)
+    #[serde(rename = "document")]
+    Document {
+        #[serde(default)]
+        source: Value,
+        #[serde(default)]
+        title: Option<String>,
+    },
+    
+    /// Tool run by the API itself (web search)
+    #[serde(rename = "server_tool_use")]
+    ServerToolUse(ToolUseBlock),
+    
+    /// Results of a server-side web search
+    #[serde(rename = "web_search_tool_result")]
+    WebSearchToolResult {
+        tool_use_id: String,
+        #[serde(default)]
+        content: Value,
+    },
+    
     /// Unknown/future content type - fallback for any unrecognized types
     #[serde(other)]
     Unknown,
 }
 
+/// Content of a tool result: plain text, or blocks (text + images...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ToolResultContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+impl Default for ToolResultContent {
+    fn default() -> Self {
+        ToolResultContent::Text(String::new())
+    }
+}
+
+impl ToolResultContent {
+    /// Text of the result (text blocks joined)
+    pub fn as_text(&self) -> Cow<'_, str> {
+        match self {
+            ToolResultContent::Text(text) => Cow::Borrowed(text),
+            ToolResultContent::Blocks(blocks) => Cow::Owned(
+                blocks.iter()
+                    .filter_map(|block| block.extract_text())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        }
+    }
+}
+
 impl ContentBlock {
     /// Extract text content from any block type
-    pub fn extract_text(&self) -> Option<&str> {
+    pub fn extract_text(&self) -> Option<Cow<'_, str>> {
         match self {
-            ContentBlock::Text { text } => Some(text),
-            ContentBlock::Thinking { thinking, .. } => Some(thinking),
-            ContentBlock::ToolResult { content, .. } => Some(content),
+            ContentBlock::Text { text } => Some(Cow::Borrowed(text)),
+            ContentBlock::Thinking { thinking, .. } => Some(Cow::Borrowed(thinking)),
+            ContentBlock::ToolResult { content, .. } => Some(content.as_text()),
             _ => None,
         }
     }
@@ -46,6 +46,8 @@ pub enum MessageContent {
     String(String),
     /// Array of structured content blocks
     Blocks(Vec<ContentBlock>),
+    /// Content we could not model: kept so the entry (and its usage) is not dropped
+    Raw(serde_json::Value),
 }
 
 impl MessageContent {
@@ -59,6 +61,7 @@ impl MessageContent {
                     .collect::<Vec<_>>()
                     .join("\n")
             }
+            MessageContent::Raw(_) => String::new(),
         }
     }
     
@@ -3,4 +3,5 @@ pub mod message;
 pub mod usage;
 pub mod content;
 pub mod tool_use;
-pub mod input;
\ No newline at end of file
+pub mod input;
+pub mod usage_line;
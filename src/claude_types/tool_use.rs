@@ -1,5 +1,7 @@
+use std::borrow::Cow;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use super::content::ToolResultContent;
 
 /// A tool use request block
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,8 +26,8 @@ pub struct ToolResultBlock {
     #[serde(default)]
     pub is_error: bool,
     
-    /// Result content (may be text, JSON, etc.)
-    pub content: Option<String>,
+    /// Result content (text, or blocks such as text + image)
+    pub content: Option<ToolResultContent>,
 }
 
 impl ToolUseBlock {
@@ -51,7 +53,7 @@ impl ToolResultBlock {
     }
     
     /// Get content as string, handling None case
-    pub fn content_or_empty(&self) -> &str {
-        self.content.as_deref().unwrap_or("")
+    pub fn content_or_empty(&self) -> Cow<'_, str> {
+        self.content.as_ref().map(|content| content.as_text()).unwrap_or_default()
     }
 }
\ No newline at end of file
@@ -4,15 +4,19 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageUsage {
     /// Input tokens consumed
+    #[serde(default)]
     pub input_tokens: u32,
     
     /// Output tokens generated
+    #[serde(default)]
     pub output_tokens: u32,
     
     /// Tokens used for cache creation
+    #[serde(default)]
     pub cache_creation_input_tokens: u32,
     
     /// Tokens read from cache
+    #[serde(default)]
     pub cache_read_input_tokens: u32,
     
     /// Server-side tool usage statistics
@@ -20,6 +24,7 @@ pub struct MessageUsage {
     pub server_tool_use: Option<ServerToolUse>,
     
     /// Service tier used (if applicable)
+    #[serde(default)]
     pub service_tier: Option<String>,
 }
 
@@ -27,6 +32,7 @@ pub struct MessageUsage {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerToolUse {
     /// Number of web search requests made
+    #[serde(default)]
     pub web_search_requests: u32,
 }
 
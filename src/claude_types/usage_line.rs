@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use super::message::MessageContent;
+use super::usage::MessageUsage;
+
+/// Lenient view of a transcript line, used by the cache builder
+/// Every field is optional: a line missing `cwd`, `uuid`, `gitBranch`... (which the full
+/// `TranscriptEntry` requires) still gets its usage counted. Unknown fields are ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UsageLine {
+    /// Type of entry (user, assistant, summary, ...)
+    #[serde(rename = "type", default)]
+    pub entry_type: Option<String>,
+
+    /// ISO timestamp when entry was created
+    #[serde(default)]
+    pub timestamp: Option<String>,
+
+    /// Session ID for this conversation
+    #[serde(rename = "sessionId", default)]
+    pub session_id: Option<String>,
+
+    /// Request ID of the API call that produced the message
+    #[serde(rename = "requestId", default)]
+    pub request_id: Option<String>,
+
+    /// Whether this represents an API error
+    #[serde(rename = "isApiErrorMessage", default)]
+    pub is_api_error_message: bool,
+
+    /// Whether this is a sidechain (subagent) conversation
+    #[serde(rename = "isSidechain", default)]
+    pub is_sidechain: bool,
+
+    /// Claude Code version
+    #[serde(default)]
+    pub version: Option<String>,
+
+    /// Message fields needed for accounting
+    #[serde(default)]
+    pub message: Option<UsageMessage>,
+}
+
+/// The parts of a message the cache builder reads
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UsageMessage {
+    /// Message ID (same for every line of a streamed response)
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Model used for this message
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Role of the message sender (user, assistant, system)
+    #[serde(default)]
+    pub role: Option<String>,
+
+    /// Token usage information
+    #[serde(default)]
+    pub usage: Option<MessageUsage>,
+
+    /// Content, kept as raw JSON when it doesn't match the known block types
+    #[serde(default)]
+    pub content: MessageContent,
+}
+
+impl UsageLine {
+    /// Parsed timestamp, None if missing or invalid
+    pub fn timestamp_utc(&self) -> Option<DateTime<Utc>> {
+        let timestamp = DateTime::parse_from_rfc3339(self.timestamp.as_deref()?).ok()?;
+        Some(timestamp.with_timezone(&Utc))
+    }
+
+    /// Usage of the message, if any
+    pub fn usage(&self) -> Option<&MessageUsage> {
+        self.message.as_ref()?.usage.as_ref()
+    }
+
+    /// Whether this line is a conversation entry (has a message) or a session summary
+    pub fn is_known(&self) -> bool {
+        self.message.is_some() || self.entry_type.as_deref() == Some("summary")
+    }
+}
@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "rs-claude-bar", about = "Track Claude usage", version)]
@@ -11,6 +11,14 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub no_save: bool,
     
+    /// Only report on this Claude root (all roots aggregated by default)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub root: Option<String>,
+    
+    /// Show times in UTC instead of the configured timezone
+    #[arg(long, global = true)]
+    pub utc: bool,
+    
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -37,9 +45,171 @@ pub enum Commands {
     Blocks{        
         #[command(subcommand)]
         command: Option<BlocksCommands>,
+        #[command(flatten)]
+        query: QueryArgs,
+    },
+    /// Inspect or export the usage cache
+    Cache {
+        #[command(subcommand)]
+        command: Option<CacheCommands>,
+    },
+    /// Inspect raw cache data (limit events, parse errors)
+    Debug {
+        #[command(subcommand)]
+        command: Option<DebugCommands>,
+    },
+    /// Tool usage analytics (calls, errors, Bash commands, edited files)
+    Tools {
+        /// Only count the last N days (unless --since is given)
+        #[arg(long, default_value_t = 30)]
+        days: i64,
+        #[command(flatten)]
+        query: QueryArgs,
+    },
+    /// Limit hit history: time to limit, lockouts, usage and projects per hit
+    Limits {
+        /// Only report limits hit in the last N days (unless --since is given)
+        #[arg(long, default_value_t = 30)]
+        days: i64,
+        #[command(flatten)]
+        query: QueryArgs,
+    },
+    /// Usage heatmap by weekday and hour of day (configured timezone)
+    Heatmap {
+        /// Only count the last N days (unless --since is given)
+        #[arg(long, default_value_t = 28)]
+        days: i64,
+        #[command(flatten)]
+        query: QueryArgs,
+        /// What each cell counts
+        #[arg(short, long, value_enum, default_value_t = HeatmapMetric::Tokens)]
+        metric: HeatmapMetric,
+        /// Print the 7×24 matrix as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check usage budgets and send alerts for thresholds crossed
+    Check {
+        /// Show the alerts that would be sent, without sending or remembering them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Compare two periods: this week vs last week by default, or two ranges
+    Compare {
+        /// Compare the current day, week or month so far with the same span of the previous one
+        #[arg(long, value_enum, default_value_t = ComparePeriod::Week)]
+        period: ComparePeriod,
+        /// Two ranges START..END to compare instead, earlier first (END defaults to now)
+        #[arg(num_args = 2, value_name = "RANGE")]
+        ranges: Vec<String>,
+        /// Number of projects and models listed
+        #[arg(long, default_value_t = 5)]
+        top: usize,
+        #[command(flatten)]
+        query: QueryArgs,
+    },
+}
+
+impl Commands {
+    /// Report filters of the command, for commands that take them
+    pub fn query_args(&self) -> Option<&QueryArgs> {
+        match self {
+            Commands::Blocks { query, .. }
+            | Commands::Tools { query, .. }
+            | Commands::Limits { query, .. }
+            | Commands::Heatmap { query, .. }
+            | Commands::Compare { query, .. } => Some(query),
+            _ => None,
+        }
+    }
+}
+
+/// Filters shared by the reporting commands (see `analyze::AnalyzerQuery`)
+#[derive(Args, Clone, Debug, Default)]
+pub struct QueryArgs {
+    /// Only count usage since: 7d, 12h, 2w, 2026-10-01, 2026-10-01T14:30 or RFC 3339
+    #[arg(long, global = true, value_name = "TIME")]
+    pub since: Option<String>,
+    /// Only count usage before this time (a date includes the whole day)
+    #[arg(long, global = true, value_name = "TIME")]
+    pub until: Option<String>,
+    /// Only count project folders containing TEXT, or matching a glob with * and ? (repeatable)
+    #[arg(short, long = "project", global = true, value_name = "TEXT")]
+    pub projects: Vec<String>,
+    /// Only count models whose name contains TEXT, e.g. opus (repeatable)
+    #[arg(long = "model", global = true, value_name = "TEXT")]
+    pub models: Vec<String>,
+    /// Only count sessions whose id starts with ID (repeatable)
+    #[arg(long = "session", global = true, value_name = "ID")]
+    pub sessions: Vec<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum HeatmapMetric {
+    /// Output tokens
+    Tokens,
+    /// User and assistant messages
+    Messages,
+}
+
+impl HeatmapMetric {
+    pub fn key(&self) -> &'static str {
+        match self {
+            HeatmapMetric::Tokens => "tokens",
+            HeatmapMetric::Messages => "messages",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HeatmapMetric::Tokens => "output tokens",
+            HeatmapMetric::Messages => "messages",
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ComparePeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl ComparePeriod {
+    /// Labels of the current and the previous period
+    pub fn labels(&self) -> (&'static str, &'static str) {
+        match self {
+            ComparePeriod::Day => ("Today", "Yesterday"),
+            ComparePeriod::Week => ("This week", "Last week"),
+            ComparePeriod::Month => ("This month", "Last month"),
+        }
+    }
+}
+
+#[derive(Subcommand, Clone)]
+pub enum CacheCommands {
+    /// Show cached files per root (including missing transcripts)
+    #[command(name = "stats")]
+    Stats,
+    /// Export the cache as JSON
+    #[command(name = "export")]
+    Export {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
     },
 }
 
+#[derive(Subcommand, Clone)]
+pub enum DebugCommands {
+    /// List limit events found in the cache
+    #[command(name = "limits")]
+    Limits,
+    /// Show transcript lines that could not be parsed
+    #[command(name = "parse")]
+    Parse,
+}
+
 #[derive(Subcommand, Clone)]
 pub enum BlocksCommands {
     /// Configure Claude data path
@@ -51,6 +221,9 @@ pub enum BlocksCommands {
     /// Configure Claude data path
     #[command(name = "gaps")]
     Gap,  
+    /// Weekly usage: 7-day windows ending at weekly resets, and the current week
+    #[command(name = "weekly")]
+    Weekly,
 }
 
 #[derive(Subcommand, Clone)]
@@ -67,6 +240,27 @@ pub enum HelpCommands {
     /// Configure Claude data path
     #[command(name = "blocks")]
     Blocks,    
+    /// Cache inspection and export guide
+    #[command(name = "cache")]
+    Cache,
+    /// Tool usage analytics guide
+    #[command(name = "tools")]
+    Tools,
+    /// Limit hit history guide
+    #[command(name = "limits")]
+    Limits,
+    /// Usage heatmap guide
+    #[command(name = "heatmap")]
+    Heatmap,
+    /// Period comparison guide
+    #[command(name = "compare")]
+    Compare,
+    /// Budgets and alerts guide
+    #[command(name = "check")]
+    Check,
+    /// Debug commands guide
+    #[command(name = "debug")]
+    Debug,
 }
 
 #[derive(Subcommand, Clone)]
@@ -77,4 +271,11 @@ pub enum ConfigCommands {
     /// Configure display settings
     #[command(name = "display")]
     Display,
+    /// Set a config value (plan, limit.output_tokens, limit.messages, limit.weekly_output_tokens)
+    #[command(name = "set")]
+    Set {
+        key: String,
+        /// New value; `auto` clears a manual limit
+        value: String,
+    },
 }
@@ -1,7 +1,7 @@
 use crate::{
-    analyze::Analyzer, 
+    analyze::{Analyzer, BlockKind, EstimateSource, MIN_LIMIT_SAMPLES}, 
     cli::BlocksCommands, 
-    common::colors::*, 
+    common::{colors::*, time::{datetime_width, format_time}}, 
     table::{
         HeaderInfo, 
         TableCreator,
@@ -10,16 +10,12 @@ use crate::{
         format_duration,
         format_token_count,
         format_number_with_separators,
+        format_text,
     }
 };
 
 /// Minimal `blocks` implementation: use Analyzer’s limit blocks and display last 10.
 pub fn run(block_cmd: Option<BlocksCommands>, analyzer: &Analyzer) {
-    println!(
-        "{bold}{cyan}📊 5-Hour Usage Blocks (limits){reset}",
-        bold = { BOLD }, cyan = { CYAN }, reset = { RESET }
-    );
-
     // Get blocks based on command
     let all_blocks = analyzer.blocks_typed_all();
     let blocks = match block_cmd {
@@ -33,6 +29,7 @@ pub fn run(block_cmd: Option<BlocksCommands>, analyzer: &Analyzer) {
                 .filter(|block| matches!(block.kind, crate::analyze::BlockKind::Limit | crate::analyze::BlockKind::Current))
                 .collect()
         },
+        Some(BlocksCommands::Weekly) => return show_weekly(analyzer),
         Some(BlocksCommands::Gap) => {
             // Filter only gap blocks + current
             all_blocks.into_iter()
@@ -61,12 +58,20 @@ pub fn run(block_cmd: Option<BlocksCommands>, analyzer: &Analyzer) {
         }
     };
 
-    // Table: Start | End | Duration | Tokens | Messages | Status (most recent first)
+    println!(
+        "{bold}{cyan}📊 5-Hour Usage Blocks (limits){reset}{}",
+        filters_label(analyzer),
+        bold = { BOLD }, cyan = { CYAN }, reset = { RESET }
+    );
+
+    // Table: Start | End | Duration | Tokens | Agents | Search | Messages | Status (most recent first)
     let headers = vec![
-        HeaderInfo::new("Start", 11),
-        HeaderInfo::new("End", 11),
+        HeaderInfo::new("Start", datetime_width()),
+        HeaderInfo::new("End", datetime_width()),
         HeaderInfo::new("Length", 7),
         HeaderInfo::new("Tokens", 6),
+        HeaderInfo::new("Agents", 6),
+        HeaderInfo::new("Search", 6),
         HeaderInfo::new("Messages", 9),
         HeaderInfo::new("Status", 10),
     ];
@@ -83,9 +88,134 @@ pub fn run(block_cmd: Option<BlocksCommands>, analyzer: &Analyzer) {
             format_date(end, 1),
             format_duration(duration, 7),
             format_token_count(tokens as u32, 6),
+            format_text(&format!("{:.0}%", b.stats.sidechain_percent()), 6),
+            format_number_with_separators(b.stats.web_search_requests as u32),
             format_number_with_separators(messages as u32),
             format_kind(&b.kind),
         ]);
     }
     tc.display(false);
+
+    let estimate = analyzer.limit_estimate();
+    println!();
+    print_current_window(analyzer);
+    match estimate.source {
+        EstimateSource::Learned => println!(
+            "Limit: {bold}{}{reset} weighted output tokens {gray}(95% CI {}–{}, {} of {} limit hits){reset}",
+            format_token_count(estimate.value as u32, 6).trim(),
+            format_token_count(estimate.low as u32, 6).trim(),
+            format_token_count(estimate.high as u32, 6).trim(),
+            estimate.samples,
+            estimate.hits,
+            bold = BOLD, gray = GRAY, reset = RESET
+        ),
+        EstimateSource::Preset if estimate.value == 0 => println!(
+            "Limit: {bold}unknown{reset} {gray}(set one with `config set plan` or `config set limit.output_tokens`, or {} of {} limit hits needed to learn it){reset}",
+            estimate.hits,
+            MIN_LIMIT_SAMPLES,
+            bold = BOLD, gray = GRAY, reset = RESET
+        ),
+        EstimateSource::Preset => println!(
+            "Limit: {bold}{}{reset} weighted output tokens {gray}(plan preset, {} of {} limit hits needed to learn it){reset}",
+            format_token_count(estimate.value as u32, 6).trim(),
+            estimate.hits,
+            MIN_LIMIT_SAMPLES,
+            bold = BOLD, gray = GRAY, reset = RESET
+        ),
+        EstimateSource::Manual => println!(
+            "Limit: {bold}{}{reset} weighted output tokens {gray}(manual, `config set limit.output_tokens`){reset}",
+            format_token_count(estimate.value as u32, 6).trim(),
+            bold = BOLD, gray = GRAY, reset = RESET
+        ),
+    }
+}
+
+/// " [since …, project: …]" when the analysis is filtered
+fn filters_label(analyzer: &Analyzer) -> String {
+    match analyzer.query().is_empty() {
+        true => String::new(),
+        false => format!(" {gray}[{}]{reset}", analyzer.query().describe(), gray = GRAY, reset = RESET),
+    }
+}
+
+/// Start, time left, last message and burn rate of the block in progress
+fn print_current_window(analyzer: &Analyzer) {
+    let current = analyzer.get_current();
+    let now = chrono::Utc::now();
+    let Some(last_message) = analyzer.last_message() else {
+        return;
+    };
+    println!(
+        "Current window: started {bold}{}{reset}, {bold}{}{reset} left {gray}·{reset} last message {} ago {gray}·{reset} {}/min",
+        format_time(current.start),
+        format_duration(current.end - now, 0).trim(),
+        format_duration(now - last_message, 0).trim(),
+        format_token_count(analyzer.burn_rate().round() as u32, 0),
+        bold = BOLD, gray = GRAY, reset = RESET
+    );
+}
+
+/// 7-day windows that ended on a weekly reset, then the current week
+fn show_weekly(analyzer: &Analyzer) {
+    println!(
+        "{bold}{cyan}📅 Weekly Usage (7-day windows){reset}{}",
+        filters_label(analyzer),
+        bold = { BOLD }, cyan = { CYAN }, reset = { RESET }
+    );
+
+    // Table: Start | End | Limit | Tokens | Opus | Messages | Status
+    let headers = vec![
+        HeaderInfo::new("Start", datetime_width()),
+        HeaderInfo::new("End", datetime_width()),
+        HeaderInfo::new("Limit", 11),
+        HeaderInfo::new("Tokens", 6),
+        HeaderInfo::new("Opus", 6),
+        HeaderInfo::new("Messages", 9),
+        HeaderInfo::new("Status", 10),
+    ];
+    let mut tc = TableCreator::new(headers);
+    for w in analyzer.weekly_blocks() {
+        let limit = match (&w.kind, w.unlock_timestamp) {
+            (BlockKind::Current, None) => "-",
+            _ => w.limit.label(),
+        };
+        let messages = w.stats.assistant_messages + w.stats.user_messages;
+
+        tc.add_row(vec![
+            format_date(w.start, 1),
+            format_date(w.end, 1),
+            format_text(limit, 11),
+            format_token_count(w.stats.output_tokens as u32, 6),
+            format_token_count(w.opus_output_tokens as u32, 6),
+            format_number_with_separators(messages as u32),
+            format_kind(&w.kind),
+        ]);
+    }
+    tc.display(false);
+
+    let week = analyzer.get_current_week();
+    let used = week.counted_output_tokens(week.limit);
+    let (max, source) = analyzer.weekly_limit(week.limit);
+    println!();
+    match week.unlock_timestamp {
+        Some(unlock) => println!(
+            "{red}{bold}🚫 {} limit reached, resets {}{reset}",
+            week.limit.label(),
+            format_date(unlock, 11),
+            red = RED, bold = BOLD, reset = RESET
+        ),
+        None if max > 0 => println!(
+            "This week: {bold}{}{reset} output tokens, {bold}{:.0}%{reset} of the weekly limit ({}, {})",
+            format_token_count(used as u32, 6).trim(),
+            100.0 * used as f64 / max as f64,
+            format_token_count(max as u32, 6).trim(),
+            source.label(),
+            bold = BOLD, reset = RESET
+        ),
+        None => println!(
+            "This week: {bold}{}{reset} output tokens {gray}(no weekly limit hit yet, limit unknown){reset}",
+            format_token_count(used as u32, 6).trim(),
+            bold = BOLD, gray = GRAY, reset = RESET
+        ),
+    }
 }
@@ -0,0 +1,124 @@
+use std::fs;
+
+use crate::{
+    cache::{export_cache_json, CacheManager, CacheStatus},
+    cli::CacheCommands,
+    common::colors::*,
+    table::{HeaderInfo, TableCreator, format_number_with_separators, format_text},
+};
+
+pub fn run(cache_cmd: Option<CacheCommands>, cache_manager: &CacheManager) {
+    match cache_cmd {
+        Some(CacheCommands::Stats) => stats(cache_manager),
+        Some(CacheCommands::Export { output }) => export(cache_manager, output),
+        None => show_cache_help(),
+    }
+}
+
+fn stats(cache_manager: &CacheManager) {
+    println!(
+        "{bold}{cyan}💾 Cache Statistics{reset}",
+        bold = BOLD, cyan = CYAN, reset = RESET
+    );
+
+    let cache = cache_manager.get_cache();
+    let mut root_names: Vec<&String> = cache.roots.keys().collect();
+    root_names.sort();
+
+    let root_width = root_names.iter().map(|name| name.len()).max().unwrap_or(4).max(4);
+    let headers = vec![
+        HeaderInfo::new("Root", root_width),
+        HeaderInfo::new("Folders", 7),
+        HeaderInfo::new("Files", 7),
+        HeaderInfo::new("Missing", 7),
+        HeaderInfo::new("Hours", 7),
+        HeaderInfo::new("Limits", 6),
+        HeaderInfo::new("Duplicates", 10),
+    ];
+    let mut tc = TableCreator::new(headers);
+
+    for name in root_names {
+        let cached_root = &cache.roots[name];
+        let files: Vec<_> = cached_root.folders.values().flat_map(|f| f.files.values()).collect();
+        let missing = files.iter().filter(|f| matches!(f.cache_status, CacheStatus::Missing)).count();
+        let hours: usize = files.iter().map(|f| f.per_hour.len()).sum();
+        let limits: usize = files.iter().map(|f| f.blocks.len()).sum();
+        let duplicates: u32 = files.iter()
+            .flat_map(|f| f.per_hour.values())
+            .map(|hour| hour.duplicate_entries)
+            .sum();
+
+        tc.add_row(vec![
+            format_text(name, root_width),
+            format_number_with_separators(cached_root.folders.len() as u32),
+            format_number_with_separators(files.len() as u32),
+            format_number_with_separators(missing as u32),
+            format_number_with_separators(hours as u32),
+            format_number_with_separators(limits as u32),
+            format_number_with_separators(duplicates),
+        ]);
+    }
+    tc.display(false);
+}
+
+fn export(cache_manager: &CacheManager, output: Option<String>) {
+    let json = export_cache_json(cache_manager.get_cache());
+    match output {
+        Some(path) => match fs::write(&path, json) {
+            Ok(_) => println!("{green}✓{reset} Cache exported to {}", path, green = GREEN, reset = RESET),
+            Err(e) => eprintln!("{red}✗{reset} Failed to write {}: {}", path, e, red = RED, reset = RESET),
+        },
+        None => println!("{}", json),
+    }
+}
+
+pub fn show_cache_help() {
+    let help_text = format!(r#"
+{bold}{cyan}💾 Cache Commands{reset}
+
+{bold}USAGE:{reset}
+    rs-claude-bar cache <SUBCOMMAND>
+
+{bold}SUBCOMMANDS:{reset}
+    {green}stats{reset}          Show cached folders, files and missing transcripts per root
+    {green}export{reset}         Export the cache as JSON (stdout or --output FILE)
+
+{bold}EXAMPLES:{reset}
+    {gray}# Inspect the cache{reset}
+    rs-claude-bar cache export | less
+
+    {gray}# Save it to a file{reset}
+    rs-claude-bar cache export --output cache.json
+
+{bold}CACHE FILE LOCATION:{reset}
+    ~/.claude-bar/cache.bin (binary, rebuilt automatically on format change)
+
+{bold}DELETED TRANSCRIPTS:{reset}
+    Claude Code deletes old transcripts (cleanupPeriodDays). Their usage is kept
+    by default; set "missing_files": "prune" in config.json to drop it instead.
+    Transcripts moved to another project folder keep their history.
+
+{bold}SUBAGENT TRANSCRIPTS:{reset}
+    Project folders are scanned recursively for *.jsonl files. Nested transcripts
+    ({cyan}<session>/subagents/agent-*.jsonl{reset}) count toward their parent session.
+
+{bold}DUPLICATES:{reset}
+    Resumed/forked sessions copy earlier messages, and streamed responses log the
+    same message several times. Each API response (message id + request id) is
+    counted once; skipped entries are listed in the Duplicates column of stats.
+
+{bold}ARCHIVE:{reset}
+    Completed hours are also appended to {cyan}~/.claude-bar/archive/YYYY-MM.jsonl{reset}
+    (per project, session and model). The archive is never pruned; when the cache
+    is rebuilt, usage of transcripts that no longer exist is restored from it.
+
+"#,
+        bold = { BOLD },
+        reset = { RESET },
+        cyan = { CYAN },
+        green = { GREEN },
+        gray = { GRAY },
+    );
+
+    print!("{}", help_text);
+}
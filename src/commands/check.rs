@@ -0,0 +1,116 @@
+use crate::{
+    alert::{check_budgets, load_alert_state, BudgetKind},
+    analyze::Analyzer,
+    cache::CacheManager,
+    common::{colors::*, time::datetime_width},
+    config::ConfigInfo,
+    table::{HeaderInfo, TableCreator, format_date, format_text, format_token_count},
+};
+
+/// Budgets with their usage, sending alerts for thresholds crossed since the last check
+pub fn run(dry_run: bool, config: &ConfigInfo, analyzer: &Analyzer, cache_manager: &CacheManager) {
+    println!("{bold}{cyan}🔔 Budgets{reset}", bold = BOLD, cyan = CYAN, reset = RESET);
+
+    let (statuses, alerts) = check_budgets(config, analyzer, cache_manager.get_cache(), dry_run);
+    if statuses.is_empty() {
+        println!("No budgets configured. Set one with `rs-claude-bar config set budget.<window_percent|day_cost|week_tokens> <N>`.");
+        return;
+    }
+
+    let state = load_alert_state();
+    let mut tc = TableCreator::new(vec![
+        HeaderInfo::new("Budget", 13),
+        HeaderInfo::new("Since", datetime_width()),
+        HeaderInfo::new("Used", 8),
+        HeaderInfo::new("Budget", 8),
+        HeaderInfo::new("%", 5),
+        HeaderInfo::new("Alerted", 7),
+    ]);
+    for status in &statuses {
+        let alerted = state.budgets.get(&status.kind)
+            .filter(|mark| mark.period_start == status.period_start)
+            .map_or_else(|| "-".to_string(), |mark| format!("{}%", mark.threshold));
+        tc.add_row(vec![
+            format!("{:<13}", status.kind.label()),
+            format_date(status.period_start, datetime_width()),
+            format_text(&format_amount(status.kind, status.value), 8),
+            format_text(&format_amount(status.kind, status.budget), 8),
+            format_text(&format!("{:.0}%", status.percent()), 5),
+            format_text(&alerted, 7),
+        ]);
+    }
+    tc.display(false);
+
+    let sinks: Vec<&str> = config.alerts.sinks.iter().map(|sink| sink.key()).collect();
+    let thresholds: Vec<String> = config.alerts.thresholds.iter().map(|threshold| format!("{}%", threshold)).collect();
+    match (alerts.len(), dry_run) {
+        (0, _) => println!(
+            "No new alerts {gray}(thresholds {}){reset}",
+            thresholds.join(", "),
+            gray = GRAY, reset = RESET
+        ),
+        (count, true) => println!("{} alert(s) would be sent {gray}(--dry-run){reset}", count, gray = GRAY, reset = RESET),
+        (count, false) => println!(
+            "{} alert(s) sent to {}",
+            count,
+            if sinks.is_empty() { "no sink".to_string() } else { sinks.join(", ") }
+        ),
+    }
+}
+
+fn format_amount(kind: BudgetKind, value: f64) -> String {
+    match kind {
+        BudgetKind::WindowPercent => format!("{:.0}%", value),
+        BudgetKind::DayCost => format!("${:.2}", value),
+        BudgetKind::WeekTokens => format_token_count(value.min(u32::MAX as f64) as u32, 0),
+    }
+}
+
+pub fn show_check_help() {
+    let help_text = format!(r#"
+{bold}{cyan}🔔 Budgets and Alerts{reset}
+
+{bold}USAGE:{reset}
+    rs-claude-bar check [--dry-run]
+
+{bold}OPTIONS:{reset}
+    {yellow}--dry-run{reset}    Show the alerts that would be sent, without sending or remembering them
+
+{bold}BUDGETS:{reset} (`config set budget.<name> <N|off>`)
+    {green}window_percent{reset}  Percent of the 5-hour limit used in the current window
+    {green}day_cost{reset}        Estimated API cost of the day, in USD
+    {green}week_tokens{reset}     Output tokens of the week (from Monday)
+
+{bold}ALERTS:{reset}
+    An alert is raised once per threshold (`alert.thresholds`, default 80,100 percent
+    of the budget) and per window, day or week; the thresholds already reported are
+    kept in ~/.claude-bar/alert_state.json. Budgets are also checked by `prompt`
+    unless `alert.on_prompt` is off.
+
+{bold}SINKS:{reset} (`config set alert.sinks stderr,notify,command,log`)
+    {green}stderr{reset}     Printed on stderr
+    {green}notify{reset}     Desktop notification via notify-send, when installed
+    {green}command{reset}    `alert.command` run by sh -c, with CLAUDE_BAR_ALERT_BUDGET, _THRESHOLD,
+               _VALUE, _LIMIT, _MESSAGE and _JSON in the environment (output discarded)
+    {green}log{reset}        One JSON line per alert appended to `alert.log`
+               (default ~/.claude-bar/alerts.jsonl)
+
+{bold}EXAMPLES:{reset}
+    {gray}# Warn at 80% and 100% of $20 a day and of 90% of each window{reset}
+    rs-claude-bar config set budget.day_cost 20
+    rs-claude-bar config set budget.window_percent 90
+
+    {gray}# Post alerts to a webhook as well{reset}
+    rs-claude-bar config set alert.command 'curl -s -d "$CLAUDE_BAR_ALERT_MESSAGE" https://ntfy.sh/my-topic'
+    rs-claude-bar config set alert.sinks stderr,notify,command
+"#,
+        bold = BOLD,
+        reset = RESET,
+        cyan = CYAN,
+        green = GREEN,
+        yellow = YELLOW,
+        gray = GRAY,
+    );
+
+    print!("{}", help_text);
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Datelike, Duration, Months, Utc};
+
+use crate::{
+    analyze::{analyze_period, parse_time_bound, AnalyzerQuery, PeriodStats},
+    cache::CacheManager,
+    cli::ComparePeriod,
+    common::{colors::*, time::{format_datetime, settings, start_of_day, to_local, week_start}},
+    table::{HeaderInfo, TableCreator, format_number_with_separators, format_path, format_text, format_token_count},
+};
+
+/// How a metric is printed
+#[derive(Clone, Copy)]
+enum Unit {
+    Tokens,
+    Count,
+    Usd,
+}
+
+/// Label, start and end of a compared period
+type Range = (&'static str, DateTime<Utc>, DateTime<Utc>);
+/// Row of the totals table: name, unit and value of a period
+type Metric = (&'static str, Unit, fn(&PeriodStats) -> f64);
+
+/// Usage of two periods side by side: totals by token type, cost, messages, limit
+/// hits and sessions, then the top projects and models, with deltas and trends
+pub fn run(
+    period: ComparePeriod,
+    ranges: &[String],
+    top: usize,
+    query: &AnalyzerQuery,
+    cache_manager: &CacheManager,
+) -> Result<(), String> {
+    if query.since.is_some() || query.until.is_some() {
+        return Err("--since/--until don't apply to compare: pass two ranges instead (e.g. 2026-09-01..2026-09-30 2026-10-01..)".to_string());
+    }
+    let now = Utc::now();
+    let [(label_a, start_a, end_a), (label_b, start_b, end_b)] = resolve_ranges(period, ranges, now)?;
+
+    let cache = cache_manager.get_cache();
+    let a = analyze_period(cache, query, start_a, end_a);
+    let b = analyze_period(cache, query, start_b, end_b);
+
+    let filters = query.describe();
+    println!(
+        "{bold}{cyan}⚖️  Usage Comparison{reset}{}",
+        if filters.is_empty() { String::new() } else { format!(" {gray}({}){reset}", filters, gray = GRAY, reset = RESET) },
+        bold = BOLD, cyan = CYAN, reset = RESET
+    );
+    for (label, period) in [(label_a, &a), (label_b, &b)] {
+        println!(
+            "  {bold}{:<10}{reset} {} → {}",
+            label,
+            format_datetime(period.start),
+            format_datetime(period.end),
+            bold = BOLD, reset = RESET
+        );
+    }
+    println!();
+
+    let mut tc = TableCreator::new(vec![
+        HeaderInfo::new("Metric", 15),
+        HeaderInfo::new(label_a, 10),
+        HeaderInfo::new(label_b, 10),
+        HeaderInfo::new("Change", 9),
+        HeaderInfo::new("Trend", 8),
+    ]);
+    let rows: [Metric; 11] = [
+        ("Input", Unit::Tokens, |p| p.stats.input_tokens as f64),
+        ("Output", Unit::Tokens, |p| p.stats.output_tokens as f64),
+        ("Cache write", Unit::Tokens, |p| p.stats.cache_creation_tokens as f64),
+        ("Cache read", Unit::Tokens, |p| p.stats.cache_read_tokens as f64),
+        ("Total tokens", Unit::Tokens, |p| p.stats.total_tokens as f64),
+        ("Weighted output", Unit::Tokens, |p| p.stats.weighted_output_tokens as f64),
+        ("Cost (API)", Unit::Usd, |p| p.cost),
+        ("Messages", Unit::Count, |p| (p.stats.assistant_messages + p.stats.user_messages) as f64),
+        ("Limit hits", Unit::Count, |p| p.limit_hits as f64),
+        ("Sessions", Unit::Count, |p| p.sessions as f64),
+        ("Web searches", Unit::Count, |p| p.stats.web_search_requests as f64),
+    ];
+    for (name, unit, value) in rows {
+        let (before, after) = (value(&a), value(&b));
+        tc.add_row(vec![
+            format!("{:<15}", name),
+            format_text(&format_value(before, unit), 10),
+            format_text(&format_value(after, unit), 10),
+            format_text(&format_change(after - before, unit), 9),
+            format_text(&format_trend(before, after), 8),
+        ]);
+    }
+    tc.display(false);
+
+    print_top("Project", &a.projects, &b.projects, (label_a, label_b), top);
+    print_top("Model", &a.models, &b.models, (label_a, label_b), top);
+    Ok(())
+}
+
+/// (label, start, end) of the earlier and the later range: explicit `ranges`, or the
+/// current `period` so far and the same span from the start of the previous one
+fn resolve_ranges(
+    period: ComparePeriod,
+    ranges: &[String],
+    now: DateTime<Utc>,
+) -> Result<[Range; 2], String> {
+    if let [first, second] = ranges {
+        let (start_a, end_a) = parse_range(first, now)?;
+        let (start_b, end_b) = parse_range(second, now)?;
+        return Ok([("Earlier", start_a, end_a), ("Later", start_b, end_b)]);
+    }
+
+    let today = to_local(now).date_naive();
+    let (current, previous) = match period {
+        ComparePeriod::Day => (today, today - Duration::days(1)),
+        ComparePeriod::Week => {
+            let monday = week_start(today);
+            (monday, monday - Duration::weeks(1))
+        }
+        ComparePeriod::Month => {
+            let first = today.with_day(1).unwrap_or(today);
+            (first, first - Months::new(1))
+        }
+    };
+    let (start_b, start_a) = (start_of_day(current), start_of_day(previous));
+    let (label_b, label_a) = period.labels();
+    let end_a = (start_a + (now - start_b)).min(start_b);
+    Ok([(label_a, start_a, end_a), (label_b, start_b, now)])
+}
+
+/// `START..END` (END defaults to now; a date END includes the whole day)
+fn parse_range(range: &str, now: DateTime<Utc>) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let Some((start, end)) = range.split_once("..") else {
+        return Err(format!("Invalid range '{}' (expected START..END, e.g. 2026-10-01..2026-10-07)", range));
+    };
+    let zone = settings().zone;
+    let start = parse_time_bound(start, now, zone, false)?;
+    let end = match end.trim() {
+        "" => now,
+        end => parse_time_bound(end, now, zone, true)?,
+    };
+    match start < end {
+        true => Ok((start, end)),
+        false => Err(format!("Invalid range '{}': it ends before it starts", range)),
+    }
+}
+
+/// Output tokens of the `top` biggest entries of either period
+fn print_top(
+    label: &str,
+    before: &HashMap<String, i64>,
+    after: &HashMap<String, i64>,
+    (label_a, label_b): (&str, &str),
+    top: usize,
+) {
+    let mut names: Vec<&String> = before.keys().chain(after.keys()).collect();
+    names.sort();
+    names.dedup();
+    let tokens = |map: &HashMap<String, i64>, name: &str| map.get(name).copied().unwrap_or(0);
+    names.retain(|name| tokens(before, name).max(tokens(after, name)) > 0);
+    names.sort_by_key(|name| std::cmp::Reverse(tokens(before, name).max(tokens(after, name))));
+    names.truncate(top);
+    if names.is_empty() {
+        return;
+    }
+
+    let width = names.iter().map(|name| name.chars().count()).max().unwrap_or(0).clamp(label.len(), 40);
+    let mut tc = TableCreator::new(vec![
+        HeaderInfo::new(label, width),
+        HeaderInfo::new(label_a, 10),
+        HeaderInfo::new(label_b, 10),
+        HeaderInfo::new("Change", 9),
+        HeaderInfo::new("Trend", 8),
+    ]);
+    for name in names {
+        let (a, b) = (tokens(before, name) as f64, tokens(after, name) as f64);
+        tc.add_row(vec![
+            format_path(name, width),
+            format_text(&format_value(a, Unit::Tokens), 10),
+            format_text(&format_value(b, Unit::Tokens), 10),
+            format_text(&format_change(b - a, Unit::Tokens), 9),
+            format_text(&format_trend(a, b), 8),
+        ]);
+    }
+    println!();
+    println!("{bold}Output tokens by {}{reset}", label.to_lowercase(), bold = BOLD, reset = RESET);
+    tc.display(false);
+}
+
+fn format_value(value: f64, unit: Unit) -> String {
+    let clamped = value.abs().round().min(u32::MAX as f64) as u32;
+    match unit {
+        Unit::Tokens => format_token_count(clamped, 0),
+        Unit::Count => format_number_with_separators(clamped),
+        Unit::Usd => format!("${:.2}", value.abs()),
+    }
+}
+
+/// Signed difference, e.g. "+1.2M", "-3", "+$4.20"
+fn format_change(delta: f64, unit: Unit) -> String {
+    let unchanged = match unit {
+        Unit::Usd => delta.abs() < 0.005,
+        _ => delta.round() == 0.0,
+    };
+    match unchanged {
+        true => "0".to_string(),
+        false => format!("{}{}", if delta > 0.0 { "+" } else { "-" }, format_value(delta, unit)),
+    }
+}
+
+/// Arrow and relative change from `before` to `after`, e.g. "↑ 12%", "↓ 40%", "→ 0%"
+fn format_trend(before: f64, after: f64) -> String {
+    if before == 0.0 {
+        return match after > 0.0 {
+            true => "↑ new".to_string(),
+            false => "-".to_string(),
+        };
+    }
+    let percent = 100.0 * (after - before) / before;
+    let arrow = match percent {
+        p if p >= 0.5 => "↑",
+        p if p <= -0.5 => "↓",
+        _ => "→",
+    };
+    format!("{} {:.0}%", arrow, percent.abs())
+}
+
+pub fn show_compare_help() {
+    let help_text = format!(r#"
+{bold}{cyan}⚖️  Usage Comparison{reset}
+
+{bold}USAGE:{reset}
+    rs-claude-bar compare [--period day|week|month] [--top N] [FILTERS]
+    rs-claude-bar compare <START..END> <START..END> [--top N] [FILTERS]
+
+{bold}OPTIONS:{reset}
+    {yellow}--period <PERIOD>{reset}   Compare the current day, week (default) or month so far with
+                        the same span from the start of the previous one
+    {yellow}<RANGES>{reset}            Two ranges to compare instead, earlier first; START and END
+                        take 7d, 2026-10-01, 2026-10-01T14:30 or RFC 3339 (END defaults to now)
+    {yellow}--top <N>{reset}           Projects and models listed (default: 5)
+    {yellow}FILTERS{reset}             --project, --model, --session (see `rs-claude-bar help`)
+
+{bold}OUTPUT:{reset}
+    - Tokens by type, weighted output, estimated API cost, messages, limit hits,
+      sessions and web searches of both periods, with the change and its trend
+    - Output tokens of the busiest projects and models of either period
+    Totals come from the hourly aggregates of the cache, like `blocks`; each hour
+    counts in the period it starts in (an hour split at 14:30 goes to the earlier
+    range). The cost uses API list prices.
+
+{bold}EXAMPLES:{reset}
+    {gray}# This week so far against the same part of last week{reset}
+    rs-claude-bar compare
+
+    {gray}# September against October, one project{reset}
+    rs-claude-bar compare 2026-09-01..2026-09-30 2026-10-01..2026-10-31 --project my-app
+
+    {gray}# Opus usage, today against yesterday{reset}
+    rs-claude-bar compare --period day --model opus
+"#,
+        bold = BOLD,
+        reset = RESET,
+        cyan = CYAN,
+        yellow = YELLOW,
+        gray = GRAY,
+    );
+
+    print!("{}", help_text);
+}
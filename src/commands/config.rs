@@ -6,6 +6,7 @@ pub fn run(config_cmd: Option<ConfigCommands>, config_manager: &mut ConfigManage
     match config_cmd {
         Some(ConfigCommands::ClaudePath) => config_manager.configure_claude(),
         Some(ConfigCommands::Display) => config_manager.configure_display(&data),
+        Some(ConfigCommands::Set { key, value }) => config_manager.set_value(&key, &value),
         None => show_config_help(), // Show help when no subcommand provided
     }
 }
@@ -20,6 +21,29 @@ pub fn show_config_help() {
 {bold}SUBCOMMANDS:{reset}
     {green}claude-path{reset}    Configure Claude data directory path
     {green}display{reset}        Configure display items and formats
+    {green}set{reset} <KEY> <VALUE>  Set a value (see KEYS)
+
+{bold}KEYS:{reset}
+    {green}plan{reset}                        pro, max5, max20, api or custom (default: no preset)
+    {green}limit.output_tokens{reset}         Weighted output tokens per 5-hour window
+    {green}limit.messages{reset}              Messages per 5-hour window
+    {green}limit.weekly_output_tokens{reset}  Output tokens per week
+    Limits are per account; `auto` goes back to learned/preset limits.
+    {green}timezone{reset}                    IANA name (Europe/Paris) or local (system zone)
+    {green}clock{reset}                       24h or 12h
+    {green}date_format{reset}                 strftime pattern for table dates, or auto
+    Times are shown in the timezone; pass --utc to see UTC for one command.
+    {green}budget.window_percent{reset}       Percent of the 5-hour limit per window, or off
+    {green}budget.day_cost{reset}             Estimated API cost per day in USD, or off
+    {green}budget.week_tokens{reset}          Output tokens per week, or off
+    {green}alert.thresholds{reset}            Percents of a budget that alert (default 80,100)
+    {green}alert.sinks{reset}                 stderr, notify, command, log (comma-separated) or none
+    {green}alert.command{reset}               Shell command of the command sink, or off
+    {green}alert.log{reset}                   JSONL file of the log sink, or default
+    {green}alert.on_prompt{reset}             on/off: also check budgets in `prompt`
+    See `rs-claude-bar help check`.
+    {green}parse_warning_percent{reset}       Unparsed usage lines after a Claude Code upgrade that
+                                show the ParseHealth warning (default 5)
 
 {bold}EXAMPLES:{reset}
     {gray}# Configure Claude data path{reset}
@@ -28,6 +52,17 @@ pub fn show_config_help() {
     {gray}# Configure display settings{reset}
     rs-claude-bar config display
 
+    {gray}# Use the Max 5x presets, but a known 5-hour limit{reset}
+    rs-claude-bar config set plan max5
+    rs-claude-bar config set limit.output_tokens 95000
+
+    {gray}# Times in Paris, 12-hour clock{reset}
+    rs-claude-bar config set timezone Europe/Paris
+    rs-claude-bar config set clock 12h
+
+    {gray}# Alert at 80% and 100% of $20 a day{reset}
+    rs-claude-bar config set budget.day_cost 20
+
 {bold}CONFIG FILE LOCATION:{reset}
     ~/.claude-bar/config.json
 
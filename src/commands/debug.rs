@@ -1,20 +1,122 @@
+use std::{cmp::Reverse, collections::BTreeMap};
+
 use crate::{
-    cache::CacheManager,
-    common::colors::*,
+    cache::{CacheManager, ParseHealth},
+    cli::DebugCommands,
+    common::{colors::*, time::datetime_width},
     table::{
         TableCreator,
         HeaderInfo,
         format_date,
+        format_number_with_separators,
+        format_path,
         format_text,
     }
 };
 
-pub fn run(cache_manager: &CacheManager, limits: bool) {
-    if limits {
-        run_limits_debug_cache(cache_manager);
+pub fn run(debug_cmd: Option<DebugCommands>, cache_manager: &CacheManager) {
+    match debug_cmd {
+        Some(DebugCommands::Limits) => run_limits_debug_cache(cache_manager),
+        Some(DebugCommands::Parse) => run_parse_debug_cache(cache_manager),
+        None => show_debug_help(),
+    }
+}
+
+/// Transcript lines the parser could not read, per Claude Code version and per file
+fn run_parse_debug_cache(cache_manager: &CacheManager) {
+    println!(
+        "{bold}{cyan}🩺 DEBUG: Parse Health (Cache-only){reset}",
+        bold = BOLD,
+        cyan = CYAN,
+        reset = RESET,
+    );
+    println!();
+
+    let mut by_version: BTreeMap<&str, ParseHealth> = BTreeMap::new();
+    let mut files_with_issues = Vec::new();
+    for (root_name, cached_root) in &cache_manager.get_cache().roots {
+        for (folder_name, cached_folder) in &cached_root.folders {
+            for (file_name, cached_file) in &cached_folder.files {
+                let mut health = ParseHealth::default();
+                for hour in cached_file.per_hour.values() {
+                    health.merge(&hour.parse_health);
+                }
+                let version = cached_file.claude_version.as_deref().unwrap_or("?");
+                by_version.entry(version).or_default().merge(&health);
+                if health.lost_usage_lines > 0 || health.failed_lines > 0 {
+                    files_with_issues.push((root_name, folder_name, file_name, version, health, &cached_file.parse_errors));
+                }
+            }
+        }
+    }
+
+    // Per version: a jump after an upgrade points to a transcript format change
+    let headers = vec![
+        HeaderInfo::new("Version", 10),
+        HeaderInfo::new("Parsed", 9),
+        HeaderInfo::new("Unknown", 9),
+        HeaderInfo::new("Failed", 9),
+        HeaderInfo::new("Lost usage", 10),
+    ];
+    let mut tc = TableCreator::new(headers);
+    let lines = |health: &ParseHealth| health.parsed_lines + health.unknown_lines + health.failed_lines;
+    for (version, health) in by_version.iter().rev().filter(|(_, health)| lines(health) > 0) {
+        tc.add_row(vec![
+            format_text(version, 10),
+            format_number_with_separators(health.parsed_lines),
+            format_number_with_separators(health.unknown_lines),
+            format_number_with_separators(health.failed_lines),
+            format_text(&format!("{:.1}%", health.lost_usage_percent()), 10),
+        ]);
+    }
+    tc.display(false);
+    println!();
+
+    if files_with_issues.is_empty() {
+        println!("✅ No usage lost to parse errors");
+        return;
+    }
+
+    files_with_issues.sort_by_key(|issue| Reverse(issue.4.lost_usage_lines));
+    for (root_name, folder_name, file_name, version, health, errors) in files_with_issues {
+        println!(
+            "{bold}{}/{}/{}{reset} {gray}(v{}, {} failed, {} with usage lost){reset}",
+            root_name, folder_name, format_path(file_name, 60).trim_start(), version,
+            health.failed_lines, health.lost_usage_lines,
+            bold = BOLD, gray = GRAY, reset = RESET,
+        );
+        for error in errors {
+            println!("    {yellow}{}{reset}", error, yellow = YELLOW, reset = RESET);
+        }
     }
 }
 
+pub fn show_debug_help() {
+    let help_text = format!(r#"
+{bold}{cyan}🐛 Debug Commands{reset}
+
+{bold}USAGE:{reset}
+    rs-claude-bar debug <SUBCOMMAND>
+
+{bold}SUBCOMMANDS:{reset}
+    {green}limits{reset}         List limit events found in the cache
+    {green}parse{reset}          Show transcript lines that could not be parsed
+
+{bold}PARSE HEALTH:{reset}
+    Lines that don't match the known transcript format are counted per file, with
+    the Claude Code version and sample errors. A jump after a Claude Code upgrade
+    means its transcript format changed and some usage is missing from reports.
+    Add the {cyan}ParseHealth{reset} status line item to get a warning when it happens.
+"#,
+        bold = BOLD,
+        reset = RESET,
+        cyan = CYAN,
+        green = GREEN,
+    );
+
+    print!("{}", help_text);
+}
+
 /// Debug limits using only cache data (no filesystem access)
 fn run_limits_debug_cache(cache_manager: &CacheManager) {
     println!(
@@ -29,10 +131,12 @@ fn run_limits_debug_cache(cache_manager: &CacheManager) {
     let mut all_block_lines = Vec::new();
 
     // Collect all block lines from all files (now stored in a HashMap keyed by timestamp)
-    for (folder_name, cached_folder) in &cache_info.folders {
-        for (file_name, cached_file) in &cached_folder.files {
-            for (ts, block_line) in &cached_file.blocks {
-                all_block_lines.push((folder_name.as_str(), file_name.as_str(), ts.clone(), block_line));
+    for (root_name, cached_root) in &cache_info.roots {
+        for (folder_name, cached_folder) in &cached_root.folders {
+            for (file_name, cached_file) in &cached_folder.files {
+                for (ts, block_line) in &cached_file.blocks {
+                    all_block_lines.push((root_name.as_str(), folder_name.as_str(), file_name.as_str(), *ts, block_line));
+                }
             }
         }
     }
@@ -43,44 +147,58 @@ fn run_limits_debug_cache(cache_manager: &CacheManager) {
     }
 
     // Sort by timestamp
-    all_block_lines.sort_by_key(|(_, _, ts, _)| ts.clone());
+    all_block_lines.sort_by_key(|(_, _, _, ts, _)| *ts);
 
     // Calculate dynamic column widths based on cache data
+    let max_root_width = all_block_lines.iter()
+        .map(|(root_name, _, _, _, _)| root_name.len())
+        .max()
+        .unwrap_or(4)
+        .max(4); // Minimum width for "Root" header
+
     let max_folder_width = all_block_lines.iter()
-        .map(|(folder_name, _, _, _)| folder_name.len())
+        .map(|(_, folder_name, _, _, _)| folder_name.len())
         .max()
         .unwrap_or(10)
         .max(8); // Minimum width for "📁 Folder" header
     
     let max_file_width = all_block_lines.iter()
-        .map(|(_, file_name, _, _)| file_name.len())
+        .map(|(_, _, file_name, _, _)| file_name.len())
         .max()
         .unwrap_or(10)
         .max(6); // Minimum width for "📄 File" header
 
+    let max_reset_width = all_block_lines.iter()
+        .map(|(_, _, _, _, block_line)| block_line.reset_text.chars().count())
+        .max()
+        .unwrap_or(4)
+        .max(4);
+
     // Display table of limit events with dynamic widths
     let headers = vec![
+        HeaderInfo::new("Root", max_root_width),
         HeaderInfo::new("Folder", max_folder_width),
         HeaderInfo::new("File", max_file_width),
-        HeaderInfo::new("Date", 11),
-        HeaderInfo::new("Unlock", 11),
-        HeaderInfo::new("When", 4),
+        HeaderInfo::new("Date", datetime_width()),
+        HeaderInfo::new("Unlock", datetime_width()),
+        HeaderInfo::new("When", max_reset_width),
     ];
     let mut tc = TableCreator::new(headers);
 
-    for (folder_name, file_name, ts, block_line) in &all_block_lines {
-        let unlock_time = if let Some(unlock) = &block_line.unlock_timestamp {
-            format_date(*unlock, 11)
+    for (root_name, folder_name, file_name, ts, block_line) in &all_block_lines {
+        let unlock_time = if let Some(unlock) = block_line.unlock_timestamp(*ts) {
+            format_date(unlock, datetime_width())
         } else {
             "Unknown".to_string()
         };
 
         tc.add_row(vec![
+            format_text(root_name, max_root_width),
             format_text(folder_name, max_folder_width),
             format_text(file_name, max_file_width),
-            format_date(*ts, 11),
+            format_date(*ts, datetime_width()),
             unlock_time,
-            format_text(&block_line.reset_text, 4),
+            format_text(&block_line.reset_text, max_reset_width),
         ]);
     }
 
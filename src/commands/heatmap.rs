@@ -0,0 +1,160 @@
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::Serialize;
+
+use crate::{
+    analyze::AnalyzerQuery,
+    cache::CacheManager,
+    cli::HeatmapMetric,
+    common::{colors::*, time::{settings, to_local}},
+    table::{format_number_with_separators, format_token_count},
+};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+/// Empty cell, then quarters of the busiest cell
+const SHADES: [&str; 5] = ["  ", "░░", "▒▒", "▓▓", "██"];
+
+/// `--json` output: matrix[weekday][hour], Monday first, in the display timezone
+#[derive(Serialize)]
+struct HeatmapExport<'a> {
+    metric: &'a str,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    projects: &'a [String],
+    models: &'a [String],
+    root: Option<&'a str>,
+    timezone: &'a str,
+    utc_offset: String,
+    weekdays: [&'a str; 7],
+    matrix: [[u64; 24]; 7],
+}
+
+/// Usage by weekday and hour of day (display timezone) of the hours selected by `query`
+pub fn run(query: &AnalyzerQuery, metric: HeatmapMetric, json: bool, cache_manager: &CacheManager) {
+    let mut matrix = [[0u64; 24]; 7];
+
+    let cache = query.apply(cache_manager.get_cache());
+    let hours = cache.roots.values()
+        .flat_map(|cached_root| cached_root.folders.values())
+        .flat_map(|folder| folder.files.values())
+        .flat_map(|file| file.per_hour.values());
+    for hour in hours {
+        let local = to_local(hour.hour_start);
+        let value = match metric {
+            HeatmapMetric::Tokens => hour.output_tokens,
+            HeatmapMetric::Messages => hour.assistant_messages + hour.user_messages,
+        };
+        matrix[local.weekday().num_days_from_monday() as usize][local.hour() as usize] += value as u64;
+    }
+
+    if json {
+        let export = HeatmapExport {
+            metric: metric.key(),
+            since: query.since,
+            until: query.until,
+            projects: &query.projects,
+            models: &query.models,
+            root: query.root(),
+            timezone: settings().zone.name(),
+            utc_offset: to_local(Utc::now()).format("%:z").to_string(),
+            weekdays: WEEKDAYS,
+            matrix,
+        };
+        println!("{}", serde_json::to_string_pretty(&export).unwrap_or_default());
+        return;
+    }
+
+    println!(
+        "{bold}{cyan}🗓️  Usage Heatmap{reset} {gray}({} by weekday and hour, {}, {}){reset}",
+        metric.label(),
+        query.describe(),
+        settings().zone.name(),
+        bold = BOLD, cyan = CYAN, gray = GRAY, reset = RESET
+    );
+
+    let max = matrix.iter().flatten().copied().max().unwrap_or(0);
+    if max == 0 {
+        println!("No usage found.");
+        return;
+    }
+
+    println!();
+    let header: String = (0..24).step_by(3).map(|hour| format!("{:<6}", hour)).collect();
+    println!("     {gray}{}{reset}", header, gray = GRAY, reset = RESET);
+    for (day, row) in matrix.iter().enumerate() {
+        let cells: String = row.iter().map(|value| SHADES[shade_level(*value, max)]).collect();
+        println!(
+            "{bold}{}{reset}  {}  {gray}{:>6}{reset}",
+            WEEKDAYS[day],
+            cells,
+            format_value(row.iter().sum(), metric),
+            bold = BOLD, gray = GRAY, reset = RESET
+        );
+    }
+
+    let (peak_day, peak_hour) = (0..7)
+        .flat_map(|day| (0..24).map(move |hour| (day, hour)))
+        .max_by_key(|(day, hour)| matrix[*day][*hour])
+        .unwrap_or((0, 0));
+    println!();
+    println!(
+        "{gray}░ ≤25%  ▒ ≤50%  ▓ ≤75%  █ ≤100% of the busiest hour:{reset} {bold}{} {:02}:00{reset} ({} {})",
+        WEEKDAYS[peak_day],
+        peak_hour,
+        format_value(max, metric),
+        metric.label(),
+        bold = BOLD, gray = GRAY, reset = RESET
+    );
+}
+
+/// 0 for no usage, else 1..=4 by quarter of `max`
+fn shade_level(value: u64, max: u64) -> usize {
+    match value {
+        0 => 0,
+        value => ((value * 4).div_ceil(max) as usize).clamp(1, 4),
+    }
+}
+
+fn format_value(value: u64, metric: HeatmapMetric) -> String {
+    let value = value.min(u32::MAX as u64) as u32;
+    match metric {
+        HeatmapMetric::Tokens => format_token_count(value, 0),
+        HeatmapMetric::Messages => format_number_with_separators(value).trim().to_string(),
+    }
+}
+
+pub fn show_heatmap_help() {
+    let help_text = format!(r#"
+{bold}{cyan}🗓️  Usage Heatmap{reset}
+
+{bold}USAGE:{reset}
+    rs-claude-bar heatmap [--days N] [--metric tokens|messages] [--json] [FILTERS]
+
+{bold}OPTIONS:{reset}
+    {yellow}--days <N>{reset}             Only count the last N days (default: 28, unless --since is given)
+    {yellow}-m, --metric <METRIC>{reset}  tokens (output tokens, default) or messages
+    {yellow}--json{reset}                 Print the 7×24 matrix as JSON (Monday first)
+    {yellow}FILTERS{reset}                --since, --until, --project, --model, --session (see `rs-claude-bar help`)
+
+{bold}OUTPUT:{reset}
+    One row per weekday, one cell per hour of day in the configured timezone,
+    shaded by quarter of the busiest hour, with the row total on the right.
+
+{bold}EXAMPLES:{reset}
+    {gray}# When do we hit it hardest?{reset}
+    rs-claude-bar heatmap
+
+    {gray}# Messages of one project over the last quarter, as JSON{reset}
+    rs-claude-bar heatmap --days 90 --project my-app --metric messages --json
+
+    {gray}# Opus output since the start of the month{reset}
+    rs-claude-bar heatmap --since 2026-10-01 --model opus
+"#,
+        bold = BOLD,
+        reset = RESET,
+        cyan = CYAN,
+        yellow = YELLOW,
+        gray = GRAY,
+    );
+
+    print!("{}", help_text);
+}
@@ -1,6 +1,10 @@
 
 use crate::{
     cli::HelpCommands,
+    commands::{
+        cache::show_cache_help, check::show_check_help, compare::show_compare_help, debug::show_debug_help, heatmap::show_heatmap_help,
+        limits::show_limits_help, tools::show_tools_help,
+    },
     common::colors::*
 };
 
@@ -10,6 +14,13 @@ pub fn run(help_cmd: Option<HelpCommands>) {
         Some(HelpCommands::Prompt) => show_prompt_help(),
         Some(HelpCommands::Install) => show_install_help(),
         Some(HelpCommands::Blocks) => show_blocks_help(),
+        Some(HelpCommands::Cache) => show_cache_help(),
+        Some(HelpCommands::Tools) => show_tools_help(),
+        Some(HelpCommands::Limits) => show_limits_help(),
+        Some(HelpCommands::Heatmap) => show_heatmap_help(),
+        Some(HelpCommands::Compare) => show_compare_help(),
+        Some(HelpCommands::Check) => show_check_help(),
+        Some(HelpCommands::Debug) => show_debug_help(),
         None => show_general_help(),
     }
 }
@@ -32,6 +43,13 @@ fn show_general_help() {
     {green}install{reset}          Setup Claude Code integration and configuration
     {green}config{reset}           Manage configuration settings
     {green}blocks{reset}           Display 5-hour usage blocks and limits
+    {green}cache{reset}            Inspect or export the usage cache
+    {green}tools{reset}            Tool usage analytics (calls, errors, commands, files)
+    {green}limits{reset}           Limit hit history (time to limit, lockouts, projects)
+    {green}heatmap{reset}          Usage by weekday and hour of day
+    {green}compare{reset}          Compare two periods (this week vs last week)
+    {green}check{reset}            Check usage budgets and send alerts
+    {green}debug{reset}            Inspect limit events and transcript parse errors
     {green}help{reset}             Show detailed help for specific commands
 
 {bold}GET HELP FOR SPECIFIC COMMANDS:{reset}
@@ -39,13 +57,31 @@ fn show_general_help() {
     rs-claude-bar help prompt       Status line integration guide
     rs-claude-bar help install      Installation and setup guide
     rs-claude-bar help blocks       Usage blocks and limits guide
+    rs-claude-bar help cache        Cache inspection and export guide
+    rs-claude-bar help tools        Tool usage analytics guide
+    rs-claude-bar help limits       Limit hit history guide
+    rs-claude-bar help heatmap      Usage heatmap guide
+    rs-claude-bar help compare      Period comparison guide
+    rs-claude-bar help check        Budgets and alerts guide
+    rs-claude-bar help debug        Limit events and parse errors
 
 {bold}GLOBAL OPTIONS:{reset}
     {yellow}--no-cache{reset}        Force bypass cache and reprocess all files
     {yellow}--no-save{reset}         Don't save cache after processing
+    {yellow}--root <NAME>{reset}     Only report on one Claude root (default: all roots)
+    {yellow}--utc{reset}             Show times in UTC instead of the configured timezone
     {yellow}-h, --help{reset}        Print help information
     {yellow}-V, --version{reset}     Print version information
 
+{bold}REPORT FILTERS:{reset} (blocks, tools, limits, heatmap; compare without --since/--until)
+    {yellow}--since <TIME>{reset}      Only count usage since 7d, 12h, 2w, 2026-10-01, 2026-10-01T14:30 or RFC 3339
+    {yellow}--until <TIME>{reset}      Only count usage before TIME (a date includes the whole day)
+    {yellow}-p, --project <TEXT>{reset} Project folders containing TEXT, or matching a glob (*, ?)
+    {yellow}--model <TEXT>{reset}      Models whose name contains TEXT, e.g. opus
+    {yellow}--session <ID>{reset}      Sessions whose id starts with ID
+    Repeat --project, --model or --session to match any of several values.
+    Dates are read in the configured timezone.
+
 {bold}QUICK START:{reset}
     1. rs-claude-bar install        # Configure Claude integration
     2. rs-claude-bar prompt         # Test status line output
@@ -86,17 +122,28 @@ fn show_config_help() {
 
 {bold}CONFIG FILES:{reset}
     - Configuration: ~/.claude-bar/config.json
-    - Cache data: ~/.claude-bar/cache.json
+    - Cache data: ~/.claude-bar/cache.bin
     - Last execution: ~/.claude-bar/last_exec
+    - Alerts already sent: ~/.claude-bar/alert_state.json
 
 {bold}DEFAULT PATHS:{reset}
     - Claude data: ~/.claude/projects/
     - Config dir: ~/.claude-bar/
+
+{bold}MULTIPLE CLAUDE ROOTS:{reset}
+    "claude_data_path" accepts a list of named roots in config.json:
+    "claude_data_path": [
+      {{ "name": "work", "path": "~/.claude-work" }},
+      {{ "name": "personal", "path": "~/.claude" }}
+    ]
+    Reports aggregate all roots; use {yellow}--root work{reset} to filter.
+    Limits are estimated per root since each account has its own quota.
 "#,
         bold = BOLD,
         reset = RESET,
         cyan = CYAN,
         green = GREEN,
+        yellow = YELLOW,
         gray = GRAY,
     );
 
@@ -195,17 +242,28 @@ fn show_blocks_help() {
         r#"{bold}{cyan}📊 Usage Blocks Help{reset}
 
 {bold}USAGE:{reset}
-    rs-claude-bar blocks [SUBCOMMAND]
+    rs-claude-bar blocks [SUBCOMMAND] [FILTERS]
 
 {bold}SUBCOMMANDS:{reset}
     {green}all{reset}               Show all usage blocks from cache
     {green}limits{reset}            Show all limit events and reset times
     {green}gaps{reset}              Show usage gaps between blocks
+    {green}weekly{reset}            Show 7-day windows and the current week
 
 {bold}5-HOUR WINDOWS:{reset}
     Claude Code enforces 5-hour usage windows for rate limiting.
     This command shows your usage patterns within these windows.
 
+{bold}FILTERS:{reset}
+    --since, --until, --project, --model and --session (see `rs-claude-bar help`)
+    restrict the usage counted in blocks. Limit events are account-wide and only
+    filtered by time, and the limit estimate always uses all usage of the root.
+
+{bold}WEEKLY LIMITS:{reset}
+    Plans also cap usage per week, overall and for Opus models.
+    Each weekly limit hit closes a 7-day window ending at its reset; the
+    mean output tokens of those windows is the learned weekly limit.
+
 {bold}EXAMPLES:{reset}
     {gray}# Show recent usage blocks{reset}
     rs-claude-bar blocks
@@ -219,9 +277,16 @@ fn show_blocks_help() {
     {gray}# Show usage gaps{reset}
     rs-claude-bar blocks gaps
 
+    {gray}# Show how close this week is to the weekly limit{reset}
+    rs-claude-bar blocks weekly
+
+    {gray}# Blocks of last week, Opus usage of one project only{reset}
+    rs-claude-bar blocks all --since 7d --project my-app --model opus
+
 {bold}OUTPUT INFORMATION:{reset}
     - Block start/end times
     - Token usage (input/output/cache)
+    - Agents: share of output tokens spent by subagents (Task tool)
     - Message counts
     - Limit events and reset times
 "#,
@@ -14,12 +14,24 @@ pub fn run() {
     rs-claude-bar install        Setup Claude integration
     rs-claude-bar config         Manage configuration
     rs-claude-bar blocks         Show usage blocks
+    rs-claude-bar cache          Inspect or export the cache
+    rs-claude-bar tools          Show tool usage analytics
+    rs-claude-bar limits         Show limit hit history
+    rs-claude-bar heatmap        Show usage by weekday and hour
+    rs-claude-bar compare        Compare this week with last week
+    rs-claude-bar check          Check usage budgets
 
 {bold}GET DETAILED HELP:{reset}
     rs-claude-bar help config    Configuration guide  
     rs-claude-bar help prompt    Status line integration
     rs-claude-bar help install   Installation guide
     rs-claude-bar help blocks    Usage blocks guide
+    rs-claude-bar help cache     Cache guide
+    rs-claude-bar help tools     Tool usage guide
+    rs-claude-bar help limits    Limit hit history guide
+    rs-claude-bar help heatmap   Usage heatmap guide
+    rs-claude-bar help compare   Period comparison guide
+    rs-claude-bar help check     Budgets and alerts guide
 "#,
         bold = { BOLD },
         reset = { RESET },
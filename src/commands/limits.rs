@@ -0,0 +1,274 @@
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    analyze::AnalyzerQuery,
+    cache::{CacheManager, CachedRoot, LimitKind, ModelUsage},
+    common::{colors::*, time::datetime_width},
+    table::{
+        HeaderInfo, TableCreator, format_date, format_duration, format_number_with_separators,
+        format_path, format_text, format_token_count,
+    },
+};
+
+/// Number of projects listed in the aggregate table
+const TOP_PROJECTS: usize = 10;
+
+/// One 5-hour window that ended on a limit
+struct LimitHit {
+    root: String,
+    start: DateTime<Utc>,
+    /// First limit message of the window
+    hit: DateTime<Utc>,
+    unlock: DateTime<Utc>,
+    /// Usage from the window start up to the hour of the hit
+    usage: ModelUsage,
+    /// Output tokens per model family (opus, sonnet, haiku, other) up to the hit
+    output_by_family: HashMap<&'static str, u32>,
+    messages: u32,
+    /// Output tokens per project active during the window
+    projects: HashMap<String, u32>,
+}
+
+impl LimitHit {
+    /// Time from the window start to the hit; None when the reset is more than
+    /// 5 hours after the hit (reset time read in the wrong timezone)
+    fn time_to_limit(&self) -> Option<Duration> {
+        (self.hit >= self.start).then(|| self.hit - self.start)
+    }
+}
+
+/// Limit hits of the period of `query` with aggregates (time to limit, lockouts, projects).
+/// With project, model or session filters, only hits those had usage in are listed.
+pub fn run(query: &AnalyzerQuery, cache_manager: &CacheManager) {
+    println!(
+        "{bold}{cyan}🚫 Limit Hits{reset} {gray}({}){reset}",
+        query.describe(),
+        bold = BOLD, cyan = CYAN, gray = GRAY, reset = RESET
+    );
+
+    let cache = query.apply(cache_manager.get_cache());
+    let mut hits: Vec<LimitHit> = cache.roots.iter()
+        .flat_map(|(name, cached_root)| collect_limit_hits(name, cached_root))
+        .filter(|hit| !query.filters_usage() || !hit.projects.is_empty())
+        .collect();
+    hits.sort_by_key(|hit| hit.hit);
+
+    if hits.is_empty() {
+        println!("No 5-hour limit hits found.");
+        return;
+    }
+
+    let show_root = cache.roots.len() > 1;
+    let mut headers = vec![
+        HeaderInfo::new("Hit", datetime_width()),
+        HeaderInfo::new("Into", 7),
+        HeaderInfo::new("Locked", 7),
+        HeaderInfo::new("Output", 6),
+        HeaderInfo::new("Input", 6),
+        HeaderInfo::new("Cache", 6),
+        HeaderInfo::new("Opus", 6),
+        HeaderInfo::new("Sonnet", 6),
+        HeaderInfo::new("Haiku", 6),
+        HeaderInfo::new("Messages", 8),
+        HeaderInfo::new("Projects", 24),
+    ];
+    if show_root {
+        headers.insert(0, HeaderInfo::new("Root", 8));
+    }
+    let mut tc = TableCreator::new(headers);
+    for hit in &hits {
+        let family = |name: &str| hit.output_by_family.get(name).copied().unwrap_or(0);
+        let mut row = vec![
+            format_date(hit.hit, datetime_width()),
+            hit.time_to_limit().map_or_else(|| format_text("-", 7), |into| format_duration(into, 7)),
+            format_duration(hit.unlock - hit.hit, 7),
+            format_token_count(hit.usage.output_tokens, 6),
+            format_token_count(hit.usage.input_tokens, 6),
+            format_token_count(hit.usage.cache_creation_tokens + hit.usage.cache_read_tokens, 6),
+            format_token_count(family("opus"), 6),
+            format_token_count(family("sonnet"), 6),
+            format_token_count(family("haiku"), 6),
+            format_number_with_separators(hit.messages),
+            format_path(&projects_summary(&hit.projects), 24),
+        ];
+        if show_root {
+            row.insert(0, format_text(&hit.root, 8));
+        }
+        tc.add_row(row);
+    }
+    tc.display(false);
+
+    print_aggregates(&hits, query.days().unwrap_or(1));
+}
+
+/// First hit and usage of every 5-hour window of one (filtered) root that ended on a limit
+fn collect_limit_hits(root: &str, cached_root: &CachedRoot) -> Vec<LimitHit> {
+    // Several messages usually report the same window: keep the first one
+    let mut windows: BTreeMap<DateTime<Utc>, DateTime<Utc>> = BTreeMap::new();
+    let files = cached_root.folders.values().flat_map(|folder| folder.files.values());
+    for file in files {
+        for (ts, block) in &file.blocks {
+            if block.kind != LimitKind::FiveHour {
+                continue;
+            }
+            if let Some(unlock) = block.unlock_timestamp(*ts) {
+                let first = windows.entry(unlock).or_insert(*ts);
+                *first = (*first).min(*ts);
+            }
+        }
+    }
+
+    windows.into_iter()
+        .map(|(unlock, hit)| {
+            let start = unlock - Duration::hours(5);
+            let mut limit_hit = LimitHit {
+                root: root.to_string(),
+                start,
+                hit,
+                unlock,
+                usage: ModelUsage::default(),
+                output_by_family: HashMap::new(),
+                messages: 0,
+                projects: HashMap::new(),
+            };
+            for (folder_name, folder) in &cached_root.folders {
+                let hours = folder.files.values()
+                    .flat_map(|file| file.per_hour.values())
+                    .filter(|hour| hour.hour_start >= start && hour.hour_start < unlock);
+                for hour in hours {
+                    *limit_hit.projects.entry(folder_name.clone()).or_default() += hour.output_tokens;
+                    // Usage "at that moment": hours after the one the limit was hit in don't count
+                    if hour.hour_start > hit {
+                        continue;
+                    }
+                    limit_hit.messages += hour.assistant_messages + hour.user_messages;
+                    for (model, usage) in &hour.models {
+                        limit_hit.usage.merge(usage);
+                        *limit_hit.output_by_family.entry(model_family(model)).or_default() += usage.output_tokens;
+                    }
+                }
+            }
+            limit_hit
+        })
+        .collect()
+}
+
+fn model_family(model: &str) -> &'static str {
+    let model = model.to_lowercase();
+    ["opus", "sonnet", "haiku"]
+        .into_iter()
+        .find(|family| model.contains(family))
+        .unwrap_or("other")
+}
+
+/// Busiest project of the window, and how many others were active
+fn projects_summary(projects: &HashMap<String, u32>) -> String {
+    let Some((top, _)) = projects.iter().max_by_key(|(name, tokens)| (**tokens, std::cmp::Reverse(name.as_str()))) else {
+        return "-".to_string();
+    };
+    match projects.len() {
+        1 => top.clone(),
+        n => format!("{} +{}", top, n - 1),
+    }
+}
+
+fn print_aggregates(hits: &[LimitHit], days: i64) {
+    let count = hits.len() as i64;
+    let average = |durations: Vec<Duration>| match durations.len() {
+        0 => "-".to_string(),
+        n => format_duration(durations.iter().sum::<Duration>() / n as i32, 0).trim().to_string(),
+    };
+    let time_to_limit = average(hits.iter().filter_map(LimitHit::time_to_limit).collect());
+    let lockout = average(hits.iter().map(|hit| hit.unlock - hit.hit).collect());
+
+    println!();
+    println!(
+        "{bold}{}{reset} hits ({:.1}/week)   time to limit: {bold}{}{reset} avg   locked out: {bold}{}{reset} avg",
+        count,
+        count as f64 * 7.0 / days.max(1) as f64,
+        time_to_limit,
+        lockout,
+        bold = BOLD, reset = RESET
+    );
+
+    // Each hit is shared among the projects active in its window by output tokens
+    let mut projects: HashMap<&str, (f64, u32, u32)> = HashMap::new();
+    for hit in hits {
+        let total: u32 = hit.projects.values().sum();
+        for (name, tokens) in &hit.projects {
+            let entry = projects.entry(name).or_default();
+            entry.0 += match total {
+                0 => 1.0 / hit.projects.len() as f64,
+                total => *tokens as f64 / total as f64,
+            };
+            entry.1 += 1;
+            entry.2 += tokens;
+        }
+    }
+    if projects.is_empty() {
+        return;
+    }
+
+    let mut projects: Vec<_> = projects.into_iter().collect();
+    projects.sort_by(|a, b| b.1.0.total_cmp(&a.1.0).then(a.0.cmp(b.0)));
+    projects.truncate(TOP_PROJECTS);
+
+    let width = projects.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0).clamp(7, 50);
+    let mut tc = TableCreator::new(vec![
+        HeaderInfo::new("Project", width),
+        HeaderInfo::new("Hit share", 9),
+        HeaderInfo::new("Windows", 7),
+        HeaderInfo::new("Output", 6),
+    ]);
+    for (name, (share, windows, tokens)) in projects {
+        tc.add_row(vec![
+            format_path(name, width),
+            format_text(&format!("{:.1}", share), 9),
+            format_number_with_separators(windows),
+            format_token_count(tokens, 6),
+        ]);
+    }
+    println!();
+    tc.display(false);
+}
+
+pub fn show_limits_help() {
+    let help_text = format!(r#"
+{bold}{cyan}🚫 Limit Hits{reset}
+
+{bold}USAGE:{reset}
+    rs-claude-bar limits [--days N] [FILTERS]
+
+{bold}OPTIONS:{reset}
+    {yellow}--days <N>{reset}    Only report limits hit in the last N days (default: 30, unless --since is given)
+    {yellow}FILTERS{reset}       --since, --until, --project, --model, --session (see `rs-claude-bar help`);
+                  with --project, --model or --session only the hits they had usage in
+
+{bold}PER HIT:{reset}
+    - Into: time from the window start to the first limit message
+    - Locked: time from the limit message to the reset
+    - Tokens by type and by model family up to the hour of the hit
+    - Messages, and the projects active during the window (busiest first)
+
+{bold}AGGREGATES:{reset}
+    - Hits per week, average time to limit and lockout
+    - Projects causing most hits: each hit is split among the projects
+      active in its window by their share of output tokens
+
+{bold}EXAMPLES:{reset}
+    {gray}# Last quarter, work account only{reset}
+    rs-claude-bar --root work limits --days 90
+
+    {gray}# Hits the my-app project contributed to in September{reset}
+    rs-claude-bar limits --since 2026-09-01 --until 2026-09-30 --project my-app
+"#,
+        bold = BOLD,
+        reset = RESET,
+        cyan = CYAN,
+        yellow = YELLOW,
+        gray = GRAY,
+    );
+
+    print!("{}", help_text);
+}
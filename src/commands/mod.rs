@@ -1,7 +1,13 @@
 pub mod blocks;
+pub mod cache;
+pub mod check;
+pub mod compare;
 pub mod config;
 pub mod debug;
+pub mod heatmap;
 pub mod help;
 pub mod info;
 pub mod install;
-pub mod prompt;
\ No newline at end of file
+pub mod limits;
+pub mod prompt;
+pub mod tools;
\ No newline at end of file
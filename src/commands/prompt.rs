@@ -1,5 +1,11 @@
-use crate::{common::colors::RESET, config::ConfigInfo, display::prompt::{generate_status_line, PromptData}};
+use crate::{
+    alert::check_budgets, analyze::Analyzer, cache::CacheManager, common::colors::RESET, config::ConfigInfo,
+    display::prompt::{generate_status_line, PromptData},
+};
 
-pub fn run(config: &ConfigInfo, data: &PromptData) {    
+pub fn run(config: &ConfigInfo, data: &PromptData, analyzer: &Analyzer, cache_manager: &CacheManager) {    
     println!("{reset}{}", generate_status_line(data, &config.display), reset = RESET);
+    if config.alerts.on_prompt {
+        check_budgets(config, analyzer, cache_manager.get_cache(), false);
+    }
 }
\ No newline at end of file
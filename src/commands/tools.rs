@@ -0,0 +1,155 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use crate::{
+    analyze::{AnalyzerQuery, DataStats},
+    cache::{CacheManager, ToolStats},
+    common::colors::*,
+    table::{HeaderInfo, TableCreator, format_number_with_separators, format_path, format_text},
+};
+
+/// Number of Bash commands / edited files listed
+const TOP_COUNT: usize = 10;
+
+/// Tool usage of the hours, projects and sessions selected by `query`
+pub fn run(query: &AnalyzerQuery, cache_manager: &CacheManager) {
+    println!(
+        "{bold}{cyan}🔧 Tool Usage{reset} {gray}({}){reset}",
+        query.describe(),
+        bold = BOLD, cyan = CYAN, gray = GRAY, reset = RESET
+    );
+
+    let cache = query.apply(cache_manager.get_cache());
+    let mut tools: HashMap<&str, ToolStats> = HashMap::new();
+    let mut bash_commands: HashMap<&str, u32> = HashMap::new();
+    let mut edited_files: HashMap<&str, u32> = HashMap::new();
+    // Server tool usage (web searches), priced by DataStats
+    let mut server_tools = DataStats::default();
+    let mut service_tiers: HashMap<&str, u32> = HashMap::new();
+
+    let hours = cache.roots.values()
+        .flat_map(|cached_root| cached_root.folders.values())
+        .flat_map(|folder| folder.files.values())
+        .flat_map(|file| file.per_hour.values());
+    for hour in hours {
+        for (tool, stats) in &hour.tools {
+            tools.entry(tool).or_default().merge(stats);
+        }
+        for (program, count) in &hour.bash_commands {
+            *bash_commands.entry(program).or_default() += count;
+        }
+        for (path, count) in &hour.edited_files {
+            *edited_files.entry(path).or_default() += count;
+        }
+        server_tools.web_search_requests += hour.web_search_requests as i64;
+        for (tier, count) in &hour.service_tiers {
+            *service_tiers.entry(tier).or_default() += count;
+        }
+    }
+
+    if tools.is_empty() {
+        println!("No tool calls found.");
+        return;
+    }
+
+    // Tools, most used first
+    let mut tools: Vec<_> = tools.into_iter().collect();
+    tools.sort_by(|a, b| b.1.calls.cmp(&a.1.calls).then(a.0.cmp(b.0)));
+    let tool_width = tools.iter().map(|(name, _)| name.len()).max().unwrap_or(4).clamp(4, 40);
+    let mut tc = TableCreator::new(vec![
+        HeaderInfo::new("Tool", tool_width),
+        HeaderInfo::new("Calls", 8),
+        HeaderInfo::new("Errors", 7),
+        HeaderInfo::new("Error %", 7),
+    ]);
+    for (name, stats) in &tools {
+        tc.add_row(vec![
+            format_text(name, tool_width),
+            format_number_with_separators(stats.calls),
+            format_number_with_separators(stats.errors),
+            format_text(&format!("{:.1}%", error_percent(stats)), 7),
+        ]);
+    }
+    tc.display(false);
+
+    // Server-side tools, billed per request
+    let mut service_tiers: Vec<_> = service_tiers.into_iter().collect();
+    service_tiers.sort_by_key(|(tier, count)| (Reverse(*count), *tier));
+    println!(
+        "🔎 Web searches: {bold}{}{reset} {gray}(~${:.2}){reset}   Service tiers: {}",
+        format_number_with_separators(server_tools.web_search_requests as u32),
+        server_tools.web_search_cost(),
+        service_tiers.iter().map(|(tier, count)| format!("{} {}", tier, count)).collect::<Vec<_>>().join(", "),
+        bold = BOLD, gray = GRAY, reset = RESET
+    );
+
+    print_top("Bash Command", bash_commands, 30);
+    print_top("Edited File", edited_files, 60);
+}
+
+fn error_percent(stats: &ToolStats) -> f64 {
+    match stats.calls {
+        0 => 0.0,
+        calls => 100.0 * stats.errors as f64 / calls as f64,
+    }
+}
+
+/// Table of the TOP_COUNT most frequent entries
+fn print_top(label: &str, counts: HashMap<&str, u32>, max_width: usize) {
+    if counts.is_empty() {
+        return;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts.truncate(TOP_COUNT);
+
+    let width = counts.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0).clamp(label.len(), max_width);
+    let mut tc = TableCreator::new(vec![
+        HeaderInfo::new(label, width),
+        HeaderInfo::new("Calls", 8),
+    ]);
+    for (name, count) in counts {
+        tc.add_row(vec![
+            format_path(name, width),
+            format_number_with_separators(count),
+        ]);
+    }
+    println!();
+    tc.display(false);
+}
+
+pub fn show_tools_help() {
+    let help_text = format!(r#"
+{bold}{cyan}🔧 Tool Usage{reset}
+
+{bold}USAGE:{reset}
+    rs-claude-bar tools [--days N] [FILTERS]
+
+{bold}OPTIONS:{reset}
+    {yellow}--days <N>{reset}    Only count the last N days (default: 30, unless --since is given)
+    {yellow}FILTERS{reset}       --since, --until, --project, --model, --session (see `rs-claude-bar help`)
+
+{bold}OUTPUT:{reset}
+    - Calls and error rate per tool (Bash, Edit, Read, mcp__* tools...)
+    - Web searches (estimated at $0.01 each) and messages per service tier
+    - Most used Bash programs ("git commit", "cargo test", "ls"...)
+    - Most edited files (Edit, MultiEdit, Write, NotebookEdit)
+
+{bold}EXAMPLES:{reset}
+    {gray}# Last week, all projects{reset}
+    rs-claude-bar tools --days 7
+
+    {gray}# One project{reset}
+    rs-claude-bar tools --project my-app
+
+    {gray}# One session{reset}
+    rs-claude-bar tools --session 3f2a9c
+"#,
+        bold = BOLD,
+        reset = RESET,
+        cyan = CYAN,
+        gray = GRAY,
+        yellow = YELLOW,
+    );
+
+    print!("{}", help_text);
+}
@@ -9,3 +9,12 @@ pub fn round_to_hour_boundary(dt: DateTime<Utc>) -> DateTime<Utc> {
         .with_nanosecond(0)
         .unwrap()
 }
+
+/// Round timestamp up to hour boundary (14:32:15 -> 15:00:00, 14:00:00 stays)
+pub fn round_up_to_hour_boundary(dt: DateTime<Utc>) -> DateTime<Utc> {
+    let floor = round_to_hour_boundary(dt);
+    match floor == dt {
+        true => dt,
+        false => floor + chrono::Duration::hours(1),
+    }
+}
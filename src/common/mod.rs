@@ -1,2 +1,3 @@
 pub mod colors;
-pub mod duration;
\ No newline at end of file
+pub mod duration;
+pub mod time;
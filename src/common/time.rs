@@ -0,0 +1,127 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::config::{utils::validate_date_format, ClockFormat, TimeConfig};
+
+/// How times are shown and read, set once at startup (see `init`)
+#[derive(Debug, Clone)]
+pub struct TimeSettings {
+    /// Zone times are displayed and bucketed in (UTC with `--utc`)
+    pub zone: Tz,
+    /// Zone Claude prints reset times in: the configured zone, even with `--utc`
+    pub reset_zone: Tz,
+    pub clock: ClockFormat,
+    /// strftime pattern replacing the default date format of tables
+    pub date_format: Option<String>,
+}
+
+impl Default for TimeSettings {
+    fn default() -> Self {
+        let zone = system_zone();
+        Self { zone, reset_zone: zone, clock: ClockFormat::default(), date_format: None }
+    }
+}
+
+impl TimeSettings {
+    /// Settings of `config.json`; `utc` (the `--utc` flag) only changes the display zone
+    pub fn from_config(config: &TimeConfig, utc: bool) -> Self {
+        let configured = config.timezone.as_deref().and_then(|name| match name.parse::<Tz>() {
+            Ok(zone) => Some(zone),
+            Err(_) => {
+                eprintln!("Unknown timezone '{}' in config.json, using the system zone", name);
+                None
+            }
+        });
+        let zone = configured.unwrap_or_else(system_zone);
+        // Hand-edited patterns are checked too: chrono panics on invalid ones when formatting
+        let date_format = config.date_format.as_deref().and_then(|pattern| match validate_date_format(pattern) {
+            Ok(pattern) => Some(pattern),
+            Err(_) => {
+                eprintln!("Invalid date format '{}' in config.json, using the default one", pattern);
+                None
+            }
+        });
+        Self {
+            zone: if utc { Tz::UTC } else { zone },
+            reset_zone: zone,
+            clock: config.clock,
+            date_format,
+        }
+    }
+}
+
+static SETTINGS: OnceLock<TimeSettings> = OnceLock::new();
+
+/// Set the process-wide settings (first call wins)
+pub fn init(settings: TimeSettings) {
+    let _ = SETTINGS.set(settings);
+}
+
+/// Current settings, the system zone with a 24h clock until `init` is called
+pub fn settings() -> &'static TimeSettings {
+    SETTINGS.get_or_init(TimeSettings::default)
+}
+
+/// Zone of this machine: `TZ`, else the OS setting, else UTC
+pub fn system_zone() -> Tz {
+    std::env::var("TZ").ok()
+        .and_then(|name| name.trim_start_matches(':').parse().ok())
+        .or_else(|| iana_time_zone::get_timezone().ok().and_then(|name| name.parse().ok()))
+        .unwrap_or(Tz::UTC)
+}
+
+/// `datetime` in the display zone
+pub fn to_local(datetime: DateTime<Utc>) -> DateTime<Tz> {
+    datetime.with_timezone(&settings().zone)
+}
+
+/// strftime pattern of a time of day, e.g. "17:05" or "5:05pm"
+pub fn time_pattern() -> &'static str {
+    match settings().clock {
+        ClockFormat::H24 => "%H:%M",
+        ClockFormat::H12 => "%-I:%M%P",
+    }
+}
+
+/// Midnight starting `date` in the display zone
+pub fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    let zone = settings().zone;
+    zone.from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+        // No midnight on that day (DST change at 00:00): the zone's first hour
+        .unwrap_or_else(|| zone.from_utc_datetime(&date.and_time(NaiveTime::MIN)))
+        .with_timezone(&Utc)
+}
+
+/// Monday of the week `date` is in
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Time of day in the display zone
+pub fn format_time(datetime: DateTime<Utc>) -> String {
+    to_local(datetime).format(time_pattern()).to_string()
+}
+
+/// Date and time in the display zone, as shown in tables
+pub fn format_datetime(datetime: DateTime<Utc>) -> String {
+    let local = to_local(datetime);
+    match (&settings().date_format, settings().clock) {
+        (Some(pattern), _) => local.format(pattern).to_string(),
+        // Zero-padded hour so that table columns keep one width
+        (None, ClockFormat::H12) => local.format("%m-%d %I:%M%P").to_string(),
+        (None, ClockFormat::H24) => local.format("%m-%d %H:%M").to_string(),
+    }
+}
+
+/// Width of a table column holding `format_datetime` values
+pub fn datetime_width() -> usize {
+    // Long and short month/day names, two-digit fields
+    [(2026, 9, 30, 22), (2026, 5, 3, 9)].into_iter()
+        .filter_map(|(year, month, day, hour)| Utc.with_ymd_and_hms(year, month, day, hour, 58, 0).single())
+        .map(|datetime| format_datetime(datetime).chars().count())
+        .max()
+        .unwrap_or(11)
+}
@@ -3,6 +3,7 @@ use crate::{
         utils::{
             load_config, 
             run_claude_config,
+            run_config_set,
             run_display_config, 
             save_config,
         },
@@ -38,4 +39,8 @@ impl ConfigManager {
     pub fn configure_claude(&mut self) {
         run_claude_config(self);
     }
+
+    pub fn set_value(&mut self, key: &str, value: &str) {
+        run_config_set(self, key, value);
+    }
 }
\ No newline at end of file
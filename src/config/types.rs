@@ -1,5 +1,6 @@
-use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
+use serde::{Deserialize, Serialize};
 
 /// Main configuration for Claude Bar application
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,23 +8,303 @@ pub struct ConfigInfo {
     /// Version of the config format
     pub version: String,
     
-    /// Path to Claude Code data directory
-    pub claude_data_path: String,
+    /// Claude Code data directory, or a list of named roots
+    pub claude_data_path: ClaudeDataPath,
     
     /// Display preferences
     pub display: StatusLineConfig,
+
+    /// What to do with cached transcripts that disappeared from disk
+    #[serde(default)]
+    pub missing_files: MissingFilePolicy,
+
+    /// Subscription plan, whose preset limit is used until enough limits were hit
+    #[serde(default)]
+    pub plan: Plan,
+
+    /// Manual limits, taking precedence over learned and preset ones
+    #[serde(default)]
+    pub limits: LimitOverrides,
+
+    /// Timezone, clock and date format of displayed times
+    #[serde(default)]
+    pub time: TimeConfig,
+
+    /// Usage budgets checked by `check` (and `prompt`)
+    #[serde(default)]
+    pub budgets: Budgets,
+
+    /// Thresholds and sinks of budget alerts
+    #[serde(default)]
+    pub alerts: AlertConfig,
+
+    /// Share of usage lines the parser may lose after a Claude Code upgrade before
+    /// the `ParseHealth` status line item warns, in percent
+    #[serde(default = "default_parse_warning_percent")]
+    pub parse_warning_percent: f64,
 }
 impl Default for ConfigInfo {
     fn default() -> Self {
         Self {
             version: "1.0".to_string(),
-            claude_data_path: "~/.claude/".to_string(),
+            claude_data_path: ClaudeDataPath::Single("~/.claude/".to_string()),
             display: StatusLineConfig::default(),
+            missing_files: MissingFilePolicy::default(),
+            plan: Plan::default(),
+            limits: LimitOverrides::default(),
+            time: TimeConfig::default(),
+            budgets: Budgets::default(),
+            alerts: AlertConfig::default(),
+            parse_warning_percent: default_parse_warning_percent(),
         }
     }
     
 }
 
+/// Where Claude Code stores its data: one directory (legacy string form)
+/// or several named roots, e.g. one per `CLAUDE_CONFIG_DIR`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ClaudeDataPath {
+    Single(String),
+    Roots(Vec<ClaudeRoot>),
+}
+
+/// A named Claude Code data directory (work, personal, devcontainer...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeRoot {
+    pub name: String,
+    pub path: String,
+}
+
+/// Root name used when `claude_data_path` is a single string
+pub const DEFAULT_ROOT_NAME: &str = "default";
+
+impl ClaudeDataPath {
+    /// All configured roots with `~` expanded
+    pub fn roots(&self) -> Vec<ClaudeRoot> {
+        match self {
+            ClaudeDataPath::Single(path) => vec![ClaudeRoot {
+                name: DEFAULT_ROOT_NAME.to_string(),
+                path: expand_home(path),
+            }],
+            ClaudeDataPath::Roots(roots) => roots
+                .iter()
+                .map(|root| ClaudeRoot {
+                    name: root.name.clone(),
+                    path: expand_home(&root.path),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Expand a leading `~` to the user's home directory
+pub(crate) fn expand_home(path: &str) -> String {
+    match path.strip_prefix('~') {
+        Some(rest) => {
+            let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+            format!("{}{}", home.to_string_lossy(), rest)
+        }
+        None => path.to_string(),
+    }
+}
+
+/// Policy for transcripts that are in the cache but no longer on disk
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingFilePolicy {
+    /// Keep their usage history (Claude Code deletes transcripts after 30 days)
+    #[default]
+    Retain,
+    /// Remove them from the cache
+    Prune,
+}
+
+/// Claude subscription plan
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Plan {
+    Pro,
+    /// Max 5x
+    Max5,
+    /// Max 20x
+    Max20,
+    /// Pay-as-you-go API key: no 5-hour or weekly windows
+    Api,
+    /// Limits only come from history and `config set limit.*` (no plan set)
+    #[default]
+    Custom,
+}
+
+/// Typical usage allowed by a plan, in Sonnet-equivalent output tokens
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlanBudget {
+    pub output_tokens: i64,
+    pub messages: i64,
+    pub weekly_output_tokens: i64,
+}
+
+impl Plan {
+    pub const ALL: [Plan; 5] = [Plan::Pro, Plan::Max5, Plan::Max20, Plan::Api, Plan::Custom];
+
+    /// Rough defaults; Anthropic does not publish exact numbers
+    pub fn budget(&self) -> Option<PlanBudget> {
+        match self {
+            Plan::Pro => Some(PlanBudget { output_tokens: 19_000, messages: 45, weekly_output_tokens: 230_000 }),
+            Plan::Max5 => Some(PlanBudget { output_tokens: 88_000, messages: 225, weekly_output_tokens: 3_700_000 }),
+            Plan::Max20 => Some(PlanBudget { output_tokens: 220_000, messages: 900, weekly_output_tokens: 15_800_000 }),
+            Plan::Api | Plan::Custom => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Plan::Pro => "Pro",
+            Plan::Max5 => "Max 5x",
+            Plan::Max20 => "Max 20x",
+            Plan::Api => "API",
+            Plan::Custom => "Custom",
+        }
+    }
+
+    /// Name used in config.json and `config set plan`
+    pub fn key(&self) -> &'static str {
+        match self {
+            Plan::Pro => "pro",
+            Plan::Max5 => "max5",
+            Plan::Max20 => "max20",
+            Plan::Api => "api",
+            Plan::Custom => "custom",
+        }
+    }
+}
+
+/// Limits set with `config set limit.<name> N` (per account and window)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LimitOverrides {
+    /// Weighted output tokens per 5-hour window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<i64>,
+    /// Messages per 5-hour window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub messages: Option<i64>,
+    /// Output tokens per week
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly_output_tokens: Option<i64>,
+}
+
+/// Time display preferences (`config set timezone|clock|date_format`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TimeConfig {
+    /// IANA zone such as "Europe/Paris"; the system zone when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub clock: ClockFormat,
+    /// strftime pattern for dates in tables (e.g. "%d/%m %H:%M")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum ClockFormat {
+    #[default]
+    #[serde(rename = "24h")]
+    H24,
+    #[serde(rename = "12h")]
+    H12,
+}
+
+/// Budgets set with `config set budget.<name> N` (unset ones are not checked)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Budgets {
+    /// Percent of the 5-hour limit (weighted output tokens) used in the current window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_percent: Option<f64>,
+    /// Estimated API cost of the current day (display timezone), in USD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub day_cost: Option<f64>,
+    /// Output tokens of the current week (from Monday, display timezone)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub week_tokens: Option<i64>,
+}
+
+/// How budget alerts are raised (`config set alert.*`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertConfig {
+    /// Percents of each budget that raise an alert, once per day, week or window
+    #[serde(default = "default_alert_thresholds")]
+    pub thresholds: Vec<u32>,
+    #[serde(default = "default_alert_sinks")]
+    pub sinks: Vec<AlertSink>,
+    /// Shell command run by the `command` sink
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// JSONL file of the `log` sink (~/.claude-bar/alerts.jsonl when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_path: Option<String>,
+    /// Also check budgets while rendering the status line
+    #[serde(default = "default_on_prompt")]
+    pub on_prompt: bool,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            thresholds: default_alert_thresholds(),
+            sinks: default_alert_sinks(),
+            command: None,
+            log_path: None,
+            on_prompt: default_on_prompt(),
+        }
+    }
+}
+
+fn default_alert_thresholds() -> Vec<u32> {
+    vec![80, 100]
+}
+
+fn default_alert_sinks() -> Vec<AlertSink> {
+    vec![AlertSink::Stderr, AlertSink::Notify]
+}
+
+fn default_on_prompt() -> bool {
+    true
+}
+
+fn default_parse_warning_percent() -> f64 {
+    5.0
+}
+
+/// Where alerts are sent
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSink {
+    /// Printed on stderr
+    Stderr,
+    /// Desktop notification through `notify-send`, when installed
+    Notify,
+    /// `alerts.command` run by `sh -c`, with the alert in `CLAUDE_BAR_ALERT_*` variables
+    Command,
+    /// Appended to a JSONL file
+    Log,
+}
+
+impl AlertSink {
+    pub const ALL: [AlertSink; 4] = [AlertSink::Stderr, AlertSink::Notify, AlertSink::Command, AlertSink::Log];
+
+    /// Name used in config.json and `config set alert.sinks`
+    pub fn key(&self) -> &'static str {
+        match self {
+            AlertSink::Stderr => "stderr",
+            AlertSink::Notify => "notify",
+            AlertSink::Command => "command",
+            AlertSink::Log => "log",
+        }
+    }
+}
+
 /// User's configuration for the status line
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatusLineConfig {
@@ -59,6 +340,8 @@ pub enum StatType {
     // Token metrics
     TokenUsage,
     TokenProgress,     // Requires limit block context    
+    WeeklyUsage,       // Output tokens of the current week vs the weekly cap
+    Sparkline,         // Recent output tokens per hour or per 5 minutes
     // Time metrics  
     TimeElapsed,
     TimeRemaining,    
@@ -66,10 +349,16 @@ pub enum StatType {
     BlockStatus,    
     // Message metrics
     MessageCount,    
+    // Share of the current window spent by subagents
+    SidechainUsage,
+    // Server-side tools (web search requests and their cost)
+    WebSearch,
     // Model info
     Model,    
     // Activity indicators
     ActivityStatus,    // Active/Idle/Limited
+    // Warning when transcripts can't be parsed (Claude Code format change)
+    ParseHealth,
 }
 
 /// How a stat should be formatted/displayed
@@ -84,6 +373,7 @@ pub enum DisplayFormat {
     ProgressBar,       // "[████████░░] 80%"  
     PercentageOnly,    // "80%"
     Ratio,             // "48.7K/70K"
+    FiveMinutes,       // "▁▂▃▅▇" per 5 minutes of the current block
     
     // Time formats
     Duration,          // "2h 15m"
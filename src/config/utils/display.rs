@@ -229,6 +229,7 @@ fn format_name(format: &DisplayFormat) -> &'static str {
         DisplayFormat::ProgressBar => "Progress Bar",
         DisplayFormat::PercentageOnly => "Percentage",
         DisplayFormat::Ratio => "Ratio",
+        DisplayFormat::FiveMinutes => "5-min Buckets",
         DisplayFormat::Duration => "Duration",
         DisplayFormat::DurationShort => "Short Time",
         DisplayFormat::StatusIcon => "Icon Only",
@@ -2,8 +2,10 @@ mod file;
 mod display;
 mod claude;
 mod registry;
+mod set;
 
 pub use file::*;
 pub use display::*;
 pub use claude::*;
-pub use registry::*;
\ No newline at end of file
+pub use registry::*;
+pub use set::*;
\ No newline at end of file
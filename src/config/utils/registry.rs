@@ -50,6 +50,33 @@ impl MetricRegistry {
             enabled_by_default: true,
         });
         
+        metrics.insert(StatType::WeeklyUsage, MetricDefinition {
+            stat_type: StatType::WeeklyUsage,
+            name: "Weekly Usage".to_string(),
+            description: "Output tokens of the last 7 days vs the weekly limit".to_string(),
+            supported_formats: vec![
+                DisplayFormat::TextWithEmoji,
+                DisplayFormat::PercentageOnly,
+                DisplayFormat::Ratio,
+                DisplayFormat::Compact,
+            ],
+            default_format: DisplayFormat::TextWithEmoji,
+            enabled_by_default: false,
+        });
+        
+        metrics.insert(StatType::Sparkline, MetricDefinition {
+            stat_type: StatType::Sparkline,
+            name: "Usage Trend".to_string(),
+            description: "Output tokens of the last 12 hours, or per 5 minutes of the current block".to_string(),
+            supported_formats: vec![
+                DisplayFormat::TextWithEmoji,
+                DisplayFormat::Compact,
+                DisplayFormat::FiveMinutes,
+            ],
+            default_format: DisplayFormat::TextWithEmoji,
+            enabled_by_default: false,
+        });
+        
         metrics.insert(StatType::TimeElapsed, MetricDefinition {
             stat_type: StatType::TimeElapsed,
             name: "Time Elapsed".to_string(),
@@ -84,11 +111,39 @@ impl MetricRegistry {
                 DisplayFormat::Text,
                 DisplayFormat::TextWithEmoji,
                 DisplayFormat::Compact,
+                DisplayFormat::Ratio,
             ],
             default_format: DisplayFormat::TextWithEmoji,
             enabled_by_default: true,
         });
         
+        metrics.insert(StatType::SidechainUsage, MetricDefinition {
+            stat_type: StatType::SidechainUsage,
+            name: "Subagent Usage".to_string(),
+            description: "Share of the current block spent by subagents".to_string(),
+            supported_formats: vec![
+                DisplayFormat::PercentageOnly,
+                DisplayFormat::TextWithEmoji,
+                DisplayFormat::Compact,
+                DisplayFormat::Text,
+            ],
+            default_format: DisplayFormat::TextWithEmoji,
+            enabled_by_default: false,
+        });
+        
+        metrics.insert(StatType::WebSearch, MetricDefinition {
+            stat_type: StatType::WebSearch,
+            name: "Web Search".to_string(),
+            description: "Web searches in current block and their cost".to_string(),
+            supported_formats: vec![
+                DisplayFormat::TextWithEmoji,
+                DisplayFormat::Compact,
+                DisplayFormat::Text,
+            ],
+            default_format: DisplayFormat::TextWithEmoji,
+            enabled_by_default: false,
+        });
+        
         metrics.insert(StatType::Model, MetricDefinition {
             stat_type: StatType::Model,
             name: "Model Name".to_string(),
@@ -127,6 +182,19 @@ impl MetricRegistry {
             enabled_by_default: false,
         });
         
+        metrics.insert(StatType::ParseHealth, MetricDefinition {
+            stat_type: StatType::ParseHealth,
+            name: "Parse Warning".to_string(),
+            description: "Warns when a Claude Code upgrade breaks parsing of usage lines (hidden otherwise)".to_string(),
+            supported_formats: vec![
+                DisplayFormat::TextWithEmoji,
+                DisplayFormat::PercentageOnly,
+                DisplayFormat::StatusIcon,
+            ],
+            default_format: DisplayFormat::TextWithEmoji,
+            enabled_by_default: false,
+        });
+        
         Self { metrics }
     }
     
@@ -140,11 +208,15 @@ impl MetricRegistry {
         metrics.sort_by_key(|m| match m.stat_type {
             StatType::TokenUsage => 0,
             StatType::TokenProgress => 1,
-            StatType::TimeElapsed => 2,
-            StatType::TimeRemaining => 3,
-            StatType::MessageCount => 4,
-            StatType::Model => 5,
-            StatType::BlockStatus => 6,
+            StatType::WeeklyUsage => 2,
+            StatType::Sparkline => 3,
+            StatType::TimeElapsed => 4,
+            StatType::TimeRemaining => 5,
+            StatType::MessageCount => 6,
+            StatType::SidechainUsage => 7,
+            StatType::WebSearch => 8,
+            StatType::Model => 9,
+            StatType::BlockStatus => 10,
             _ => 99,
         });
         metrics
@@ -0,0 +1,135 @@
+use chrono_tz::Tz;
+
+use crate::{common::colors::*, config::{AlertSink, ClockFormat, ConfigManager, Plan}};
+
+/// Keys accepted by `config set`
+const KEYS: &[&str] = &[
+    "plan", "limit.output_tokens", "limit.messages", "limit.weekly_output_tokens",
+    "timezone", "clock", "date_format",
+    "budget.window_percent", "budget.day_cost", "budget.week_tokens",
+    "alert.thresholds", "alert.sinks", "alert.command", "alert.log", "alert.on_prompt",
+    "parse_warning_percent",
+];
+
+pub fn run_config_set(config_manager: &mut ConfigManager, key: &str, value: &str) {
+    match apply(config_manager, key, value.trim()) {
+        Ok(()) => {
+            config_manager.save_config();
+            println!("{green}✓{reset} {} = {yellow}{}{reset}", key, value,
+                green = GREEN, yellow = YELLOW, reset = RESET);
+        }
+        Err(message) => println!("{red}✗{reset} {}", message, red = RED, reset = RESET),
+    }
+}
+
+fn apply(config_manager: &mut ConfigManager, key: &str, value: &str) -> Result<(), String> {
+    let config = &mut config_manager.config;
+    match key {
+        "plan" => {
+            config.plan = Plan::ALL.into_iter()
+                .find(|plan| plan.key() == value.to_lowercase())
+                .ok_or_else(|| format!("Unknown plan '{}' (expected {})", value,
+                    Plan::ALL.map(|plan| plan.key()).join(", ")))?;
+        }
+        "limit.output_tokens" => config.limits.output_tokens = parse_limit(value)?,
+        "limit.messages" => config.limits.messages = parse_limit(value)?,
+        "limit.weekly_output_tokens" => config.limits.weekly_output_tokens = parse_limit(value)?,
+        "timezone" => {
+            config.time.timezone = match value {
+                "local" | "auto" => None,
+                name => Some(name.parse::<Tz>()
+                    .map_err(|_| format!("Unknown timezone '{}' (expected an IANA name such as Europe/Paris, or 'local')", name))?
+                    .name().to_string()),
+            };
+        }
+        "clock" => {
+            config.time.clock = match value.to_lowercase().as_str() {
+                "24h" | "24" => ClockFormat::H24,
+                "12h" | "12" => ClockFormat::H12,
+                _ => return Err(format!("Invalid clock '{}' (expected 24h or 12h)", value)),
+            };
+        }
+        "date_format" => {
+            config.time.date_format = match value {
+                "auto" => None,
+                pattern => Some(validate_date_format(pattern)?),
+            };
+        }
+        "budget.window_percent" => config.budgets.window_percent = parse_budget(value)?,
+        "budget.day_cost" => config.budgets.day_cost = parse_budget(value)?,
+        "budget.week_tokens" => config.budgets.week_tokens = parse_budget(value)?.map(|tokens| tokens.round() as i64),
+        "alert.thresholds" => config.alerts.thresholds = parse_thresholds(value)?,
+        "alert.sinks" => {
+            config.alerts.sinks = match value {
+                "none" => Vec::new(),
+                list => list.split(',')
+                    .map(|name| AlertSink::ALL.into_iter()
+                        .find(|sink| sink.key() == name.trim().to_lowercase())
+                        .ok_or_else(|| format!("Unknown sink '{}' (expected {}, or none)", name.trim(),
+                            AlertSink::ALL.map(|sink| sink.key()).join(", "))))
+                    .collect::<Result<_, _>>()?,
+            };
+        }
+        "alert.command" => config.alerts.command = (value != "off" && !value.is_empty()).then(|| value.to_string()),
+        "alert.log" => config.alerts.log_path = (value != "default" && !value.is_empty()).then(|| value.to_string()),
+        "alert.on_prompt" => {
+            config.alerts.on_prompt = match value.to_lowercase().as_str() {
+                "on" | "true" | "yes" => true,
+                "off" | "false" | "no" => false,
+                _ => return Err(format!("Invalid value '{}' (expected on or off)", value)),
+            };
+        }
+        "parse_warning_percent" => {
+            config.parse_warning_percent = match value.trim_end_matches('%').parse::<f64>() {
+                Ok(percent) if percent > 0.0 && percent <= 100.0 => percent,
+                _ => return Err(format!("Invalid percent '{}' (expected a number between 0 and 100)", value)),
+            };
+        }
+        _ => return Err(format!("Unknown key '{}' (expected {})", key, KEYS.join(", "))),
+    }
+    Ok(())
+}
+
+/// A strftime pattern chrono can format, e.g. "%d/%m %H:%M"
+pub fn validate_date_format(pattern: &str) -> Result<String, String> {
+    use chrono::format::{Item, StrftimeItems};
+    if pattern.is_empty() || StrftimeItems::new(pattern).any(|item| item == Item::Error) {
+        return Err(format!("Invalid date format '{}' (expected a strftime pattern such as %d/%m %H:%M, or 'auto')", pattern));
+    }
+    Ok(pattern.to_string())
+}
+
+/// A positive number, or `auto` to clear the manual limit
+fn parse_limit(value: &str) -> Result<Option<i64>, String> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(None);
+    }
+    match value.replace(['_', ','], "").parse::<i64>() {
+        Ok(limit) if limit > 0 => Ok(Some(limit)),
+        _ => Err(format!("Invalid limit '{}' (expected a positive number or 'auto')", value)),
+    }
+}
+
+/// A positive amount (percent, USD or tokens), or `off` to remove the budget
+pub fn parse_budget(value: &str) -> Result<Option<f64>, String> {
+    if value.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    match value.trim_start_matches('$').replace(['_', ','], "").parse::<f64>() {
+        Ok(budget) if budget.is_finite() && budget > 0.0 => Ok(Some(budget)),
+        _ => Err(format!("Invalid budget '{}' (expected a positive number or 'off')", value)),
+    }
+}
+
+/// Comma-separated percents of a budget, e.g. "50,80,100"
+pub fn parse_thresholds(value: &str) -> Result<Vec<u32>, String> {
+    let mut thresholds = value.split(',')
+        .map(|part| match part.trim().trim_end_matches('%').parse::<u32>() {
+            Ok(threshold) if threshold > 0 => Ok(threshold),
+            _ => Err(format!("Invalid thresholds '{}' (expected percents such as 80,100)", value)),
+        })
+        .collect::<Result<Vec<u32>, String>>()?;
+    thresholds.sort();
+    thresholds.dedup();
+    Ok(thresholds)
+}
@@ -1,6 +1,6 @@
 use std::cmp::min;
 
-use crate::{common::colors::{BOLD, GREEN, RED, RESET, YELLOW}, config::{DisplayFormat, StatType}, display::prompt::PromptData};
+use crate::{analyze::EstimateSource, common::{colors::{BOLD, GRAY, GREEN, RED, RESET, YELLOW}, time::{time_pattern, to_local}}, config::{DisplayFormat, StatType}, display::prompt::PromptData};
 
 
 /// Generate a realistic example using data data
@@ -9,11 +9,16 @@ pub fn generate_stat_with_format(data: &PromptData, stat_type: &StatType, displa
     match stat_type {
         StatType::TokenUsage => generate_token_with_format(data, display),
         StatType::TokenProgress => generate_progress_with_format(data, display),
+        StatType::WeeklyUsage => generate_weekly_with_format(data, display),
+        StatType::Sparkline => generate_sparkline_with_format(data, display),
         StatType::TimeElapsed => generate_elapsed_with_format(data, display),
         StatType::TimeRemaining => generate_remaining_with_format(data, display),
         StatType::MessageCount => generate_message_with_format(data, display),
+        StatType::SidechainUsage => generate_sidechain_with_format(data, display),
+        StatType::WebSearch => generate_web_search_with_format(data, display),
         StatType::Model => generate_model_with_format(data, display),
         StatType::BlockStatus => generate_status_with_format(data, display),       
+        StatType::ParseHealth => generate_parse_health_with_format(data, display),
 
         /*
         (StatType::ActivityStatus, DisplayFormat::StatusIcon) => {
@@ -38,9 +43,13 @@ fn generate_token_with_format(data: &PromptData, display: &DisplayFormat) -> Str
     match display {
         DisplayFormat::TextWithEmoji => format!("🧠 {}", format_number(data.tokens_used)),
         DisplayFormat::Compact => format_number_compact(data.tokens_used),
-        DisplayFormat::Ratio => format!("{bold}{}/{}{reset}", 
+        DisplayFormat::Ratio => format!("{bold}{}/{}{reset}{}", 
             format_number_compact(data.tokens_used), 
-            format_number_compact(data.tokens_limit),
+            match data.tokens_limit {
+                0 => "?".to_string(),
+                limit => format_number_compact(limit),
+            },
+            source_tag(&data.tokens_limit_source),
             reset = {RESET},
             bold = {BOLD}),
         _ => format!("{} tokens", format_number(data.tokens_used)),
@@ -48,6 +57,10 @@ fn generate_token_with_format(data: &PromptData, display: &DisplayFormat) -> Str
 }
 
 fn generate_progress_with_format(data: &PromptData, display: &DisplayFormat) -> String  {
+    // No plan preset and no limit hit or manual limit yet
+    if data.tokens_limit <= 0 {
+        return format!("{gray}limit unknown{reset}", gray = GRAY, reset = RESET);
+    }
     match display {        
         // Token Progress Examples  
         DisplayFormat::ProgressBar => {
@@ -59,10 +72,11 @@ fn generate_progress_with_format(data: &PromptData, display: &DisplayFormat) ->
                 50.0..=79.0  => YELLOW,
                 _ => RED
             };
-            format!("{color}[{}{}] {bold}{:.1}{reset}%",
+            format!("{color}[{}{}] {bold}{:.1}{reset}%{}",
                 "█".repeat(filled), 
                 "░".repeat(empty), 
                 data.progress_percent,
+                source_tag(&data.tokens_limit_source),
                 color = {color}, 
                 reset = {RESET},
                 bold = {BOLD}
@@ -73,10 +87,61 @@ fn generate_progress_with_format(data: &PromptData, display: &DisplayFormat) ->
             else if data.progress_percent < 80.0 { "🟡 Near Limit" }
             else { "🔴 Close to Limit" }
         }.to_string(),
-        _ =>  format!("{:.1}%", data.progress_percent),
+        _ =>  format!("{:.1}%{}", data.progress_percent, source_tag(&data.tokens_limit_source)),
+    }
+}
+
+/// Dim letter telling where a limit comes from: Learned, Preset or Manual
+fn source_tag(source: &EstimateSource) -> String {
+    format!(" {gray}{}{reset}", source.indicator(), gray = GRAY, reset = RESET)
+}
+
+/// Percentage only once a weekly limit has been hit (the limit is learned from it)
+fn generate_weekly_with_format(data: &PromptData, display: &DisplayFormat) -> String  {
+    if let Some(unlock) = data.weekly_unlock {
+        return format!("{red}📅 limited until {}{reset}", to_local(unlock).format(&format!("%a {}", time_pattern())), red = RED, reset = RESET);
+    }
+    let used = format_number_compact(data.weekly_tokens_used);
+    if data.weekly_tokens_limit <= 0 {
+        return match display {
+            DisplayFormat::TextWithEmoji => format!("📅 {}", used),
+            _ => used,
+        };
+    }
+    match display {
+        DisplayFormat::PercentageOnly => format!("{:.0}%", data.weekly_progress_percent),
+        DisplayFormat::Ratio => format!("{}/{}{}", used, format_number_compact(data.weekly_tokens_limit), source_tag(&data.weekly_limit_source)),
+        DisplayFormat::Compact => used,
+        _ => format!("📅 {} ({:.0}%)", used, data.weekly_progress_percent),
+    }
+}
+
+/// 5-minute slots shown at most (the most recent ones), to keep the status line short
+const SPARKLINE_MAX_SLOTS: usize = 24;
+
+fn generate_sparkline_with_format(data: &PromptData, display: &DisplayFormat) -> String  {
+    match display {
+        DisplayFormat::FiveMinutes => {
+            let skip = data.five_minute_output.len().saturating_sub(SPARKLINE_MAX_SLOTS);
+            sparkline(&data.five_minute_output[skip..])
+        }
+        DisplayFormat::Compact => sparkline(&data.hourly_output),
+        _ => format!("📈 {}", sparkline(&data.hourly_output)),
     }
 }
 
+/// One bar per value, by quarter of the largest one ("▁" for no usage)
+pub fn sparkline(values: &[i64]) -> String {
+    const BARS: [char; 5] = ['▁', '▂', '▃', '▅', '▇'];
+    let max = values.iter().copied().max().unwrap_or(0);
+    values.iter()
+        .map(|&value| match value {
+            value if value <= 0 || max <= 0 => BARS[0],
+            value => BARS[((value * 4 + max - 1) / max).clamp(1, 4) as usize],
+        })
+        .collect()
+}
+
 fn generate_elapsed_with_format(data: &PromptData, display: &DisplayFormat) -> String  {
     match display {
         DisplayFormat::Duration => format!("{}h {:02}m", data.time_elapsed_hours, data.time_elapsed_minutes),
@@ -97,9 +162,27 @@ fn generate_message_with_format(data: &PromptData, display: &DisplayFormat) -> S
     match display {
         DisplayFormat::TextWithEmoji => format!("💬 {}", data.message_count),
         DisplayFormat::Compact => format!("{}", data.message_count),
+        DisplayFormat::Ratio if data.messages_limit > 0 => format!("💬 {}/{}", data.message_count, data.messages_limit),
         _ => format!("{} messages", data.message_count),
     }
 }
+
+fn generate_sidechain_with_format(data: &PromptData, display: &DisplayFormat) -> String  {
+    match display {
+        DisplayFormat::PercentageOnly => format!("{:.0}%", data.sidechain_percent),
+        DisplayFormat::TextWithEmoji => format!("🤖 {:.0}%", data.sidechain_percent),
+        DisplayFormat::Compact => format_number_compact(data.sidechain_tokens),
+        _ => format!("{} agent tokens", format_number(data.sidechain_tokens)),
+    }
+}
+
+fn generate_web_search_with_format(data: &PromptData, display: &DisplayFormat) -> String  {
+    match display {
+        DisplayFormat::TextWithEmoji => format!("🔎 {} (${:.2})", data.web_search_requests, data.web_search_cost),
+        DisplayFormat::Compact => format!("{}", data.web_search_requests),
+        _ => format!("{} web searches (${:.2})", data.web_search_requests, data.web_search_cost),
+    }
+}
         
 fn generate_status_with_format(data: &PromptData, display: &DisplayFormat) -> String  {
     match display {
@@ -117,6 +200,19 @@ fn generate_status_with_format(data: &PromptData, display: &DisplayFormat) -> St
     }        
 }
 
+/// Empty while parsing is healthy, so the segment only shows up after an upgrade
+/// that broke it (see `parse_warning_percent`)
+fn generate_parse_health_with_format(data: &PromptData, display: &DisplayFormat) -> String  {
+    let Some(drift) = &data.parse_drift else {
+        return String::new();
+    };
+    match display {
+        DisplayFormat::StatusIcon => "⚠️".to_string(),
+        DisplayFormat::PercentageOnly => format!("{yellow}{:.0}%{reset}", drift.lost_usage_percent, yellow = YELLOW, reset = RESET),
+        _ => format!("{yellow}⚠️ {:.0}% unparsed since {}{reset}", drift.lost_usage_percent, drift.version, yellow = YELLOW, reset = RESET),
+    }
+}
+
 fn generate_model_with_format(data: &PromptData, display: &DisplayFormat) -> String  {
     match display {
         _ => format!("🤖 {}", data.model_name)
@@ -1,23 +1,48 @@
 use std::io::{self, Read};
 
+use chrono::{DateTime, Utc};
+
 use crate::{
     claude_types::input::ClaudeCodeInput, 
     config::StatusLineConfig, 
     display::generate_stat_with_format,
-    analyze::{Analyzer}
+    analyze::{Analyzer, EstimateSource, ParseDrift}
 };
 
 
 /// Mock data for testing configuration UI
 pub struct PromptData {
+    /// Weighted (Sonnet-equivalent) output tokens of the current block and the estimated limit
     pub tokens_used: i64,
     pub tokens_limit: i64,
+    /// Where tokens_limit comes from (learned, plan preset or manual)
+    pub tokens_limit_source: EstimateSource,
     pub progress_percent: f64,
     pub time_elapsed_hours: i32,
     pub time_elapsed_minutes: i32,
     pub time_remaining_hours: i32,
     pub time_remaining_minutes: i32,
     pub message_count: i64,
+    /// Messages allowed per 5-hour window (0 = unknown)
+    pub messages_limit: i64,
+    /// Output tokens of the current week, the learned weekly limit (0 = unknown)
+    /// and the pending weekly reset when the weekly cap was hit
+    pub weekly_tokens_used: i64,
+    pub weekly_tokens_limit: i64,
+    pub weekly_limit_source: EstimateSource,
+    pub weekly_progress_percent: f64,
+    pub weekly_unlock: Option<DateTime<Utc>>,
+    /// Output tokens per hour (last hours) and per 5 minutes of the current block, oldest first
+    pub hourly_output: Vec<i64>,
+    pub five_minute_output: Vec<i64>,
+    /// Output tokens of the current block spent by subagents
+    pub sidechain_tokens: i64,
+    pub sidechain_percent: f64,
+    /// Usage lines lost since the last Claude Code upgrade, when past the warning threshold
+    pub parse_drift: Option<ParseDrift>,
+    /// Web searches of the current block and their estimated cost (USD)
+    pub web_search_requests: i64,
+    pub web_search_cost: f64,
     pub model_name: String,
     pub block_status: String,
     pub is_limited: bool,
@@ -30,18 +55,42 @@ impl  PromptData {
             .map(|input| input.model.display_name)
             .unwrap_or_else(|| "Claude".to_string());
         let current = analyze.get_current();
-        let current_token = current.stats.output_tokens;
-        let max_token = analyze.output_token_max();
-        let percent = 100.0 * current_token as f64 / max_token as f64;
+        let now = Utc::now();
+        let elapsed = (now - current.start).max(chrono::Duration::zero());
+        let remaining = (current.end - now).max(chrono::Duration::zero());
+        let current_token = current.stats.weighted_output_tokens;
+        let limit = analyze.limit_estimate();
+        let max_token = limit.value;
+        let percent = match max_token {
+            0 => 0.0,
+            max => 100.0 * current_token as f64 / max as f64,
+        };
+        let week = analyze.get_current_week();
+        let weekly_used = week.counted_output_tokens(week.limit);
+        let (weekly_max, weekly_source) = analyze.weekly_limit(week.limit);
         Self {
-            tokens_used: current.stats.output_tokens,
+            tokens_used: current_token,
             tokens_limit: max_token,
+            tokens_limit_source: limit.source,
             progress_percent: percent,
-            time_elapsed_hours: 2,
-            time_elapsed_minutes: 12,
-            time_remaining_hours: 3,
-            time_remaining_minutes: 23,
+            time_elapsed_hours: elapsed.num_hours() as i32,
+            time_elapsed_minutes: (elapsed.num_minutes() % 60) as i32,
+            time_remaining_hours: remaining.num_hours() as i32,
+            time_remaining_minutes: (remaining.num_minutes() % 60) as i32,
             message_count: current.stats.assistant_messages + current.stats.user_messages,
+            messages_limit: analyze.message_limit().0,
+            weekly_tokens_used: weekly_used,
+            weekly_tokens_limit: weekly_max,
+            weekly_limit_source: weekly_source,
+            weekly_progress_percent: 100.0 * weekly_used as f64 / weekly_max.max(1) as f64,
+            weekly_unlock: week.unlock_timestamp,
+            hourly_output: analyze.recent_usage().hourly.clone(),
+            five_minute_output: analyze.recent_usage().five_minutes.clone(),
+            sidechain_tokens: current.stats.sidechain_output_tokens,
+            sidechain_percent: current.stats.sidechain_percent(),
+            parse_drift: analyze.parse_drift().cloned(),
+            web_search_requests: current.stats.web_search_requests,
+            web_search_cost: current.stats.web_search_cost(),
             model_name: model_name,
             block_status: "ACTIVE".to_string(),
             is_limited: current.unlock_timestamp.is_some(),
@@ -54,12 +103,26 @@ impl Default for PromptData {
         Self {
             tokens_used: 15234,
             tokens_limit: 28400,
+            tokens_limit_source: EstimateSource::Learned,
             progress_percent: 53.6,
             time_elapsed_hours: 2,
             time_elapsed_minutes: 15,
             time_remaining_hours: 2,
             time_remaining_minutes: 45,
             message_count: 48,
+            messages_limit: 225,
+            weekly_tokens_used: 412_000,
+            weekly_tokens_limit: 1_250_000,
+            weekly_limit_source: EstimateSource::Preset,
+            weekly_progress_percent: 33.0,
+            weekly_unlock: None,
+            hourly_output: vec![0, 0, 1200, 3400, 5100, 800, 0, 0, 2600, 6900, 4300, 1500],
+            five_minute_output: vec![300, 900, 1400, 600, 0, 0, 250, 1800, 2200, 700],
+            sidechain_tokens: 3120,
+            sidechain_percent: 20.5,
+            parse_drift: None,
+            web_search_requests: 4,
+            web_search_cost: 0.04,
             model_name: "Claude 3.5 Sonnet".to_string(),
             block_status: "ACTIVE".to_string(),
             is_limited: false,
@@ -84,6 +147,8 @@ pub fn generate_status_line(data: &PromptData, prompt_config: &StatusLineConfig)
     let prompt_parts: Vec<String> = prompt_config.items.iter()
         .filter(|item| item.enabled)
         .map(|item| generate_stat_with_format(data, &item.stat_type, &item.format))
+        // Segments with nothing to report (e.g. parse warning) are left out
+        .filter(|part| !part.is_empty())
         .collect();
     
     if prompt_parts.is_empty() {
@@ -1,5 +1,7 @@
 // Public modules that can be used as crate::module_name::*
+pub mod alert;
 pub mod analyze;
+pub mod archive;
 pub mod claude_types;
 pub mod cache;
 pub mod config;
@@ -2,14 +2,15 @@ use clap::Parser;
 use std::time::Instant;
 use std::fs;
 use std::path::PathBuf;
-use chrono::Utc;
+use chrono::{Duration, Utc};
 
 use rs_claude_bar::config::ConfigManager;
 use rs_claude_bar::cache::CacheManager;
-use rs_claude_bar::analyze::Analyzer;
+use rs_claude_bar::analyze::{Analyzer, AnalyzerQuery};
 use rs_claude_bar::cli::{Cli, Commands};
 use rs_claude_bar::display::prompt::PromptData;
 use rs_claude_bar::commands::{self};
+use rs_claude_bar::common::time::{self, TimeSettings};
 
 fn main() {
     let start = Instant::now();
@@ -21,10 +22,32 @@ fn main() {
 
     // Parse CLI first to get global flags
     let cli = Cli::parse();
+    time::init(TimeSettings::from_config(&config.time, cli.utc));
     
+    let roots = config.claude_data_path.roots();
+    if let Some(root) = &cli.root {
+        if !roots.iter().any(|r| &r.name == root) {
+            eprintln!("Unknown Claude root '{}' (configured: {})",
+                root,
+                roots.iter().map(|r| r.name.as_str()).collect::<Vec<_>>().join(", "));
+            std::process::exit(2);
+        }
+    }
+
+    // Report filters (--since, --project, ...), read in the display timezone
+    let query_args = cli.command.as_ref().and_then(Commands::query_args).cloned().unwrap_or_default();
+    let query = match AnalyzerQuery::from_args(&query_args, cli.root.as_deref()) {
+        Ok(query) => query,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let since_days = |days: i64| query.clone().since_default(Utc::now() - Duration::days(days));
+
     let cache = Instant::now();
     // Load cache (will automatically scan projects subdirectory)
-    let mut cache_manager = CacheManager::new(&config.claude_data_path, cli.no_cache);
+    let mut cache_manager = CacheManager::new(&config, cli.no_cache);
     let cache_duration = cache.elapsed();
 
     let file = Instant::now();
@@ -32,19 +55,32 @@ fn main() {
     let file_duration = file.elapsed();
 
     let analyze =  Instant::now();
-    let analyzer = Analyzer::new(cache_manager.get_cache());
+    let analyzer = Analyzer::new(cache_manager.get_cache(), &query, &config);
     let prompt_data = PromptData::new(&analyzer);
     let analyze_duration = analyze.elapsed();
 
     let exec = Instant::now();
+    let mut exit_code = 0;
     // Execute the command  
     match cli.command.unwrap_or(Commands::Info) {
         Commands::Info => commands::info::run(),
         Commands::Install => commands::install::run(),        
         Commands::Help { command } => commands::help::run(command),
-        Commands::Prompt => commands::prompt::run(&config, &prompt_data),
+        Commands::Prompt => commands::prompt::run(&config, &prompt_data, &analyzer, &cache_manager),
         Commands::Config { command } => commands::config::run(command, &mut config_manager, &prompt_data),
-        Commands::Blocks { command } => commands::blocks::run(command, &analyzer),
+        Commands::Blocks { command, .. } => commands::blocks::run(command, &analyzer),
+        Commands::Cache { command } => commands::cache::run(command, &cache_manager),
+        Commands::Debug { command } => commands::debug::run(command, &cache_manager),
+        Commands::Tools { days, .. } => commands::tools::run(&since_days(days), &cache_manager),
+        Commands::Limits { days, .. } => commands::limits::run(&since_days(days), &cache_manager),
+        Commands::Heatmap { days, metric, json, .. } => commands::heatmap::run(&since_days(days), metric, json, &cache_manager),
+        Commands::Check { dry_run } => commands::check::run(dry_run, &config, &analyzer, &cache_manager),
+        Commands::Compare { period, ranges, top, .. } => {
+            if let Err(e) = commands::compare::run(period, &ranges, top, &query, &cache_manager) {
+                eprintln!("{}", e);
+                exit_code = 2;
+            }
+        }
     }    
     let exec_duration = exec.elapsed();
 
@@ -74,4 +110,8 @@ fn main() {
         total_duration.as_secs_f64() * 1000.0,
     );
     let _ = fs::write(path, content);
+
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
 }
@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 
-/// Format a UTC datetime using "%m-%d %H:%M", right-aligned to `size` width
+use crate::common::time::format_datetime;
+
+/// Format a datetime in the display timezone ("%m-%d %H:%M" by default, see
+/// `common::time`), right-aligned to `size` width
 pub fn format_date(datetime: DateTime<Utc>, size: usize) -> String {
-    let formatted = datetime.format("%m-%d %H:%M").to_string();
+    let formatted = format_datetime(datetime);
     format!("{:>width$}", formatted, width = size)
 }
\ No newline at end of file
@@ -1,4 +1,14 @@
 /// Format a UTC datetime using "%m-%d %H:%M", right-aligned to `size` width
 pub fn format_text(text: &str, size: usize) -> String {
     format!("{:>width$}", text, width = size)
-}
\ No newline at end of file
+}
+
+/// Right-align a path to `size` width, keeping its end ("…/src/main.rs") when too long
+pub fn format_path(path: &str, size: usize) -> String {
+    let len = path.chars().count();
+    if len <= size {
+        return format_text(path, size);
+    }
+    let tail: String = path.chars().skip(len + 1 - size).collect();
+    format!("…{}", tail)
+}
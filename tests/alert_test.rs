@@ -0,0 +1,126 @@
+mod fixtures;
+
+use std::{fs, thread, time::Instant};
+
+use chrono::{DateTime, Duration, Utc};
+use rs_claude_bar::{
+    alert::{new_alerts, send_alert, Alert, AlertState, BudgetKind, BudgetStatus},
+    config::{utils::{parse_budget, parse_thresholds}, AlertConfig, AlertSink},
+};
+
+use fixtures::{now, temp_dir};
+
+fn day_cost(value: f64, period_start: DateTime<Utc>) -> BudgetStatus {
+    BudgetStatus { kind: BudgetKind::DayCost, budget: 20.0, value, period_start }
+}
+
+#[test]
+fn test_alert_once_per_threshold() {
+    let mut state = AlertState::default();
+    let today = now() - Duration::hours(18);
+
+    assert!(new_alerts(&[day_cost(10.0, today)], &[80, 100], &mut state, now()).is_empty());
+
+    let alerts = new_alerts(&[day_cost(17.0, today)], &[80, 100], &mut state, now());
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].threshold, 80);
+    // Still above 80%: no second alert
+    assert!(new_alerts(&[day_cost(18.0, today)], &[80, 100], &mut state, now()).is_empty());
+
+    let alerts = new_alerts(&[day_cost(21.0, today)], &[80, 100], &mut state, now());
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].threshold, 100);
+    assert!(new_alerts(&[day_cost(30.0, today)], &[80, 100], &mut state, now()).is_empty());
+}
+
+#[test]
+fn test_highest_threshold_crossed_at_once() {
+    let mut state = AlertState::default();
+    let alerts = new_alerts(&[day_cost(25.0, now())], &[50, 80, 100], &mut state, now());
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].threshold, 100);
+}
+
+#[test]
+fn test_new_period_starts_over() {
+    let mut state = AlertState::default();
+    let yesterday = now() - Duration::hours(42);
+    let today = now() - Duration::hours(18);
+    assert_eq!(new_alerts(&[day_cost(21.0, yesterday)], &[80, 100], &mut state, now()).len(), 1);
+    let alerts = new_alerts(&[day_cost(17.0, today)], &[80, 100], &mut state, now());
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].threshold, 80);
+}
+
+fn alert(threshold: u32) -> Alert {
+    Alert {
+        timestamp: now(),
+        budget: BudgetKind::DayCost,
+        threshold,
+        value: 21.5,
+        limit: 20.0,
+        period_start: now() - Duration::hours(18),
+        message: "Today's usage at $21.50".to_string(),
+    }
+}
+
+#[test]
+fn test_log_sink_appends_json_lines() {
+    let path = temp_dir("alert-log").join("nested").join("alerts.jsonl");
+    let config = AlertConfig { log_path: Some(path.to_string_lossy().to_string()), ..Default::default() };
+
+    send_alert(AlertSink::Log, &alert(80), &config);
+    send_alert(AlertSink::Log, &alert(100), &config);
+
+    let content = fs::read_to_string(&path).unwrap();
+    let lines: Vec<serde_json::Value> = content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["budget"], "day_cost");
+    assert_eq!(lines[0]["threshold"], 80);
+    assert_eq!(lines[1]["threshold"], 100);
+    assert_eq!(lines[1]["message"], "Today's usage at $21.50");
+}
+
+#[test]
+fn test_command_sink_environment() {
+    let path = temp_dir("alert-command").join("env.txt");
+    let config = AlertConfig {
+        command: Some(format!(
+            r#"echo ignored; printf '%s %s %s|%s' "$CLAUDE_BAR_ALERT_BUDGET" "$CLAUDE_BAR_ALERT_THRESHOLD" "$CLAUDE_BAR_ALERT_VALUE" "$CLAUDE_BAR_ALERT_MESSAGE" > '{}'"#,
+            path.display()
+        )),
+        ..Default::default()
+    };
+
+    send_alert(AlertSink::Command, &alert(100), &config);
+
+    // The command is not waited for
+    let deadline = Instant::now() + std::time::Duration::from_secs(5);
+    let mut content = String::new();
+    while content.is_empty() && Instant::now() < deadline {
+        thread::sleep(std::time::Duration::from_millis(20));
+        content = fs::read_to_string(&path).unwrap_or_default();
+    }
+    assert_eq!(content, "day_cost 100 21.50|Today's usage at $21.50");
+}
+
+#[test]
+fn test_parse_thresholds() {
+    assert_eq!(parse_thresholds("100, 80%,50"), Ok(vec![50, 80, 100]));
+    assert_eq!(parse_thresholds("80,80"), Ok(vec![80]));
+    assert!(parse_thresholds("0").is_err());
+    assert!(parse_thresholds("80,high").is_err());
+    assert!(parse_thresholds("").is_err());
+}
+
+#[test]
+fn test_parse_budget() {
+    assert_eq!(parse_budget("$20"), Ok(Some(20.0)));
+    assert_eq!(parse_budget("1_000_000"), Ok(Some(1_000_000.0)));
+    assert_eq!(parse_budget("off"), Ok(None));
+    assert!(parse_budget("0").is_err());
+    // Would be saved as null
+    assert!(parse_budget("inf").is_err());
+    assert!(parse_budget("infinity").is_err());
+    assert!(parse_budget("NaN").is_err());
+}
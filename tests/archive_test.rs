@@ -0,0 +1,78 @@
+mod fixtures;
+
+use std::fs;
+
+use chrono::Duration;
+use rs_claude_bar::{
+    archive::{archive_completed_hours, load_archive},
+    cache::{BlockLine, CacheInfo, CachedFile, LimitKind, PerHourBlock},
+};
+
+use fixtures::{cache_with, cached_file, now, temp_dir};
+
+fn hour(hours_ago: i64, output_tokens: u32) -> PerHourBlock {
+    let hour_start = now() - Duration::hours(hours_ago);
+    let mut hour = PerHourBlock::new(hour_start, hour_start);
+    hour.output_tokens = output_tokens;
+    hour.message_ids.push(output_tokens as u64);
+    hour
+}
+
+fn file_mut(cache: &mut CacheInfo) -> &mut CachedFile {
+    cache.roots.get_mut("personal").unwrap().folders.get_mut("-proj").unwrap().files.get_mut("s1.jsonl").unwrap()
+}
+
+/// Lines of every month file of the archive
+fn archived_lines(dir: &std::path::Path) -> usize {
+    fs::read_dir(dir).unwrap()
+        .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap().lines().count())
+        .sum()
+}
+
+#[test]
+fn test_archive_round_trip() {
+    let dir = temp_dir("archive-round-trip");
+    let mut file = cached_file("s1.jsonl");
+    file.session_id = Some("s1".to_string());
+    for hour in [hour(3, 100), hour(2, 200), hour(0, 400)] {
+        file.per_hour.insert(hour.hour_start, hour);
+    }
+    let hit = now() - Duration::minutes(150);
+    file.blocks.insert(hit, BlockLine { reset_text: "5pm".to_string(), kind: LimitKind::FiveHour });
+    let mut cache = cache_with(file);
+
+    archive_completed_hours(&mut cache, &dir, now());
+    // The current hour is left for later
+    assert_eq!(archived_lines(&dir), 3);
+    assert_eq!(file_mut(&mut cache).archived_until, Some(now()));
+
+    let mut restored = CacheInfo::default();
+    load_archive(&mut restored, &dir);
+    let file = file_mut(&mut restored);
+    assert_eq!(file.session_id.as_deref(), Some("s1"));
+    assert_eq!(file.archived_until, Some(now() - Duration::hours(1)));
+    let mut hours: Vec<(i64, u32, Vec<u64>)> = file.per_hour.values()
+        .map(|hour| ((now() - hour.hour_start).num_hours(), hour.output_tokens, hour.message_ids.clone()))
+        .collect();
+    hours.sort();
+    assert_eq!(hours, vec![(2, 200, vec![200]), (3, 100, vec![100])]);
+    assert_eq!(file.blocks.get(&hit).map(|block| block.reset_text.as_str()), Some("5pm"));
+}
+
+#[test]
+fn test_repeated_runs_append_once() {
+    let dir = temp_dir("archive-repeated");
+    let mut file = cached_file("s1.jsonl");
+    file.per_hour.insert(now() - Duration::hours(2), hour(2, 200));
+    let mut cache = cache_with(file);
+
+    archive_completed_hours(&mut cache, &dir, now());
+    archive_completed_hours(&mut cache, &dir, now() + Duration::minutes(30));
+    assert_eq!(archived_lines(&dir), 1);
+
+    // An hour later the previous hour is complete: only it is appended
+    file_mut(&mut cache).per_hour.insert(now(), hour(0, 400));
+    archive_completed_hours(&mut cache, &dir, now() + Duration::hours(1));
+    archive_completed_hours(&mut cache, &dir, now() + Duration::hours(1));
+    assert_eq!(archived_lines(&dir), 2);
+}
@@ -0,0 +1,97 @@
+mod fixtures;
+
+use chrono::Duration;
+use rs_claude_bar::cache::{
+    decode_cache, encode_cache, BlockLine, CacheInfo, LimitKind, ModelUsage, ParseHealth, PerHourBlock, ToolStats,
+    UsageEvent, CACHE_SCHEMA_VERSION,
+};
+
+use fixtures::{cache_with, cached_file, now};
+
+/// Cache with every field of every cached type set
+fn populated_cache() -> CacheInfo {
+    let hour_start = now() - Duration::hours(2);
+    let mut hour = PerHourBlock::new(hour_start, hour_start + Duration::minutes(3));
+    hour.max_timestamp = hour_start + Duration::minutes(58);
+    hour.input_tokens = 1_200;
+    hour.output_tokens = 4_500;
+    hour.add_slot_output(hour_start + Duration::minutes(3), 4_000);
+    hour.add_slot_output(hour_start + Duration::minutes(58), 500);
+    hour.cache_creation_tokens = 30_000;
+    hour.cache_read_tokens = 250_000;
+    hour.assistant_messages = 7;
+    hour.user_messages = 3;
+    hour.total_content_length = 12_345;
+    hour.entry_count = 11;
+    let opus = ModelUsage { input_tokens: 1_000, output_tokens: 4_000, cache_creation_tokens: 30_000, cache_read_tokens: 200_000, messages: 5 };
+    let sonnet = ModelUsage { input_tokens: 200, output_tokens: 500, cache_creation_tokens: 0, cache_read_tokens: 50_000, messages: 2 };
+    hour.models.insert("claude-opus-4-1".to_string(), opus);
+    hour.models.insert("claude-sonnet-4-5".to_string(), sonnet.clone());
+    hour.sidechain = sonnet;
+    hour.web_search_requests = 2;
+    hour.service_tiers.insert("standard".to_string(), 7);
+    hour.tools.insert("Bash".to_string(), ToolStats { calls: 4, errors: 1 });
+    hour.tools.insert("mcp__github__create_issue".to_string(), ToolStats { calls: 1, errors: 0 });
+    hour.bash_commands.insert("git commit".to_string(), 2);
+    hour.edited_files.insert("/src/main.rs".to_string(), 3);
+    hour.parse_health = ParseHealth { parsed_lines: 11, unknown_lines: 1, failed_lines: 2, usage_lines: 7, lost_usage_lines: 1 };
+    hour.duplicate_entries = 4;
+    hour.message_ids = vec![1, u64::MAX, 42];
+
+    let mut file = cached_file("s1/subagents/agent-1.jsonl");
+    file.cache_time = now() - Duration::minutes(5);
+    file.modified_time = now() - Duration::minutes(6);
+    file.per_hour.insert(hour_start, hour);
+    file.per_hour.insert(now(), PerHourBlock::new(now(), now()));
+    file.blocks.insert(hour_start + Duration::minutes(30), BlockLine { reset_text: "5pm (Europe/Paris)".to_string(), kind: LimitKind::FiveHour });
+    file.blocks.insert(hour_start + Duration::minutes(40), BlockLine { reset_text: "Mon 9am".to_string(), kind: LimitKind::WeeklyOpus });
+    file.session_id = Some("s1".to_string());
+    file.missing_since = Some(now() - Duration::days(1));
+    file.archived_until = Some(now() - Duration::hours(1));
+    file.claude_version = Some("2.0.14".to_string());
+    file.parse_errors = vec!["line 3: missing field `type`".to_string(), "ünïcödé ∙ error".to_string()];
+    file.recent_events = vec![
+        UsageEvent { timestamp: hour_start + Duration::minutes(3), output_tokens: 4_000, model: Some("claude-opus-4-1".to_string()) },
+        UsageEvent { timestamp: hour_start + Duration::minutes(4), output_tokens: 0, model: None },
+    ];
+
+    let mut cache = cache_with(file);
+    cache.roots.entry("work".to_string()).or_default();
+    cache
+}
+
+#[test]
+fn test_round_trip() {
+    let cache = populated_cache();
+    let decoded = decode_cache(&encode_cache(&cache)).expect("decodable cache");
+
+    // The JSON export covers every persisted field but the modification time
+    assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&cache).unwrap());
+    let file = |cache: &CacheInfo| cache.roots["personal"].folders["-proj"].files["s1/subagents/agent-1.jsonl"].modified_time;
+    assert_eq!(file(&decoded), file(&cache));
+    // Encoding is stable
+    assert_eq!(encode_cache(&decoded).len(), encode_cache(&cache).len());
+}
+
+#[test]
+fn test_truncated_input_is_rejected() {
+    let bytes = encode_cache(&populated_cache());
+    for len in 0..bytes.len() {
+        assert!(decode_cache(&bytes[..len]).is_none(), "decoded {} of {} bytes", len, bytes.len());
+    }
+    // Trailing bytes are rejected too
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert!(decode_cache(&longer).is_none());
+}
+
+#[test]
+fn test_other_version_is_rejected() {
+    let mut bytes = encode_cache(&populated_cache());
+    bytes[4..6].copy_from_slice(&(CACHE_SCHEMA_VERSION + 1).to_le_bytes());
+    assert!(decode_cache(&bytes).is_none());
+
+    let mut bytes = encode_cache(&populated_cache());
+    bytes[..4].copy_from_slice(b"JSON");
+    assert!(decode_cache(&bytes).is_none());
+}
@@ -0,0 +1,121 @@
+mod fixtures;
+
+use std::fs;
+
+use chrono::{DateTime, Duration, Utc};
+
+use rs_claude_bar::{
+    analyze::AnalyzerQuery,
+    cache::{refresh_single_file, CachedFile, MessageIndex, ToolStats},
+    common::duration::round_to_hour_boundary,
+    claude_types::{
+        content::{ContentBlock, ToolResultContent},
+        message::MessageContent,
+        usage_line::UsageLine,
+    },
+};
+
+use fixtures::{cache_with, cached_file, temp_dir, utc};
+
+const TOOL_USE: &str = r#"{"type":"assistant","sessionId":"s1","timestamp":"2026-10-18T10:05:00Z","requestId":"r1","message":{"id":"m1","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"tool_use","id":"toolu_1","name":"Read","input":{"file_path":"/tmp/shot.png"}}],"usage":{"input_tokens":10,"output_tokens":100}}}"#;
+/// Result of a Read on an image: an array of text and image blocks, flagged as an error
+const TOOL_RESULT: &str = r#"{"type":"user","sessionId":"s1","timestamp":"2026-10-18T10:05:30Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","is_error":true,"content":[{"type":"text","text":"Image too large"},{"type":"image","source":{"type":"base64","media_type":"image/png","data":"iVBORw0KGgo="}}]}]}}"#;
+/// Content of a shape no block type matches
+const UNKNOWN_CONTENT: &str = r#"{"type":"assistant","sessionId":"s1","timestamp":"2026-10-18T10:06:00Z","requestId":"r2","message":{"id":"m2","role":"assistant","model":"claude-sonnet-4-5","content":{"parts":[{"kind":"hologram"}]},"usage":{"input_tokens":20,"output_tokens":50}}}"#;
+
+#[test]
+fn test_tool_result_with_block_content() {
+    let line: UsageLine = serde_json::from_str(TOOL_RESULT).unwrap();
+    let MessageContent::Blocks(blocks) = &line.message.unwrap().content else {
+        panic!("content blocks expected");
+    };
+    let [ContentBlock::ToolResult { tool_use_id, content: ToolResultContent::Blocks(result), is_error }] = blocks.as_slice() else {
+        panic!("tool_result with block content expected, got {:?}", blocks);
+    };
+    assert_eq!(tool_use_id, "toolu_1");
+    assert!(*is_error);
+    assert!(matches!(result.as_slice(), [ContentBlock::Text { .. }, ContentBlock::Image { .. }]));
+    assert_eq!(blocks[0].extract_text().as_deref(), Some("Image too large"));
+}
+
+#[test]
+fn test_unknown_content_falls_back_to_raw() {
+    let line: UsageLine = serde_json::from_str(UNKNOWN_CONTENT).unwrap();
+    assert_eq!(line.usage().map(|usage| usage.output_tokens), Some(50));
+    assert!(matches!(line.message.unwrap().content, MessageContent::Raw(_)));
+}
+
+#[test]
+fn test_usage_counted_whatever_the_content() {
+    let path = temp_dir("content").join("s1.jsonl");
+    fs::write(&path, [TOOL_USE, TOOL_RESULT, UNKNOWN_CONTENT].join("\n")).unwrap();
+
+    let mut file = cached_file("s1.jsonl");
+    refresh_single_file(&mut file, &path, &mut MessageIndex::default());
+    let [hour] = file.per_hour.values().collect::<Vec<_>>()[..] else {
+        panic!("one hour expected");
+    };
+    assert_eq!(hour.output_tokens, 150);
+    assert_eq!(hour.input_tokens, 30);
+    assert_eq!((hour.assistant_messages, hour.user_messages), (2, 1));
+    assert_eq!(hour.parse_health.lost_usage_lines, 0);
+    let read = &hour.tools["Read"];
+    assert_eq!((read.calls, read.errors), (1, 1));
+}
+
+#[test]
+fn test_tool_error_after_refresh() {
+    let path = temp_dir("tool-error-refresh").join("s1.jsonl");
+    let at = |time: DateTime<Utc>| time.to_rfc3339();
+    let hour = round_to_hour_boundary(Utc::now()) - Duration::hours(1);
+    let call = TOOL_USE.replace("2026-10-18T10:05:00Z", &at(hour - Duration::minutes(5)));
+    let result = TOOL_RESULT.replace("2026-10-18T10:05:30Z", &at(hour + Duration::minutes(5)));
+
+    // The call is parsed by one refresh, its result by the next one
+    let mut file = cached_file("s1.jsonl");
+    let mut index = MessageIndex::default();
+    fs::write(&path, &call).unwrap();
+    refresh_single_file(&mut file, &path, &mut index);
+    assert!(file.open_tool_calls.contains_key("toolu_1"));
+    file.cache_time = hour;
+    fs::write(&path, [call, result].join("\n")).unwrap();
+    refresh_single_file(&mut file, &path, &mut index);
+
+    // The error is counted with its call, in the hour before the result
+    let read = |file: &CachedFile, hour_start| file.per_hour[&hour_start].tools.get("Read").cloned().unwrap_or_default();
+    let read_hour = read(&file, hour - Duration::hours(1));
+    assert_eq!((read_hour.calls, read_hour.errors), (1, 1));
+    assert_eq!(read(&file, hour).errors, 0);
+    // Answered before the hour the next refresh starts from
+    assert!(file.open_tool_calls.is_empty());
+
+    // Parsing the result again doesn't count its error twice
+    file.cache_time = hour;
+    refresh_single_file(&mut file, &path, &mut index);
+    assert_eq!(read(&file, hour - Duration::hours(1)).errors, 1);
+}
+
+#[test]
+fn test_tool_error_in_period_of_its_call() {
+    let path = temp_dir("tool-error-period").join("s1.jsonl");
+    let call = TOOL_USE.replace("2026-10-18T10:05:00Z", "2026-10-18T10:59:00Z");
+    let result = TOOL_RESULT.replace("2026-10-18T10:05:30Z", "2026-10-18T11:00:30Z");
+    fs::write(&path, [call, result].join("\n")).unwrap();
+    let mut file = cached_file("s1.jsonl");
+    refresh_single_file(&mut file, &path, &mut MessageIndex::default());
+    let cache = cache_with(file);
+
+    let read = |since, until| {
+        let query = AnalyzerQuery { since: Some(since), until: Some(until), ..AnalyzerQuery::default() };
+        let mut read = ToolStats::default();
+        for file in query.apply(&cache).roots["personal"].folders["-proj"].files.values() {
+            for hour in file.per_hour.values() {
+                read.merge(&hour.tools.get("Read").cloned().unwrap_or_default());
+            }
+        }
+        (read.calls, read.errors)
+    };
+    assert_eq!(read(utc(2026, 10, 18, 10, 0), utc(2026, 10, 18, 11, 0)), (1, 1));
+    // No error without its call
+    assert_eq!(read(utc(2026, 10, 18, 11, 0), utc(2026, 10, 18, 12, 0)), (0, 0));
+}
@@ -0,0 +1,92 @@
+mod fixtures;
+
+use chrono::Duration;
+use rs_claude_bar::{
+    archive::{ArchiveRecord, ArchiveSource},
+    cache::{refresh_single_file, MessageIndex, PerHourBlock},
+};
+
+use fixtures::{assistant_line, cache_with, cached_file, now, output_tokens, temp_dir, write_transcript};
+
+#[test]
+fn test_claim_and_release() {
+    let (old_hour, new_hour) = (now() - Duration::hours(2), now() - Duration::hours(1));
+    let mut file = cached_file("s1.jsonl");
+    for (hour_start, id) in [(old_hour, 1), (new_hour, 2)] {
+        let mut hour = PerHourBlock::new(hour_start, hour_start);
+        hour.message_ids.push(id);
+        file.per_hour.insert(hour_start, hour);
+    }
+
+    let mut index = MessageIndex::build(&cache_with(file.clone()));
+    assert!(!index.claim(1));
+    assert!(!index.claim(2));
+    assert!(index.claim(3));
+    assert!(!index.claim(3));
+
+    // Hours from the boundary on are re-parsed: their messages can be claimed again
+    index.release(&file, new_hour);
+    assert!(!index.claim(1));
+    assert!(index.claim(2));
+}
+
+#[test]
+fn test_forked_transcript_counted_once() {
+    let dir = temp_dir("dedup-fork");
+    let original = write_transcript(&dir, "a.jsonl", &[
+        assistant_line("a", "2026-10-18T10:05:00Z", "m1", 100),
+        assistant_line("a", "2026-10-18T10:10:00Z", "m2", 200),
+    ]);
+    // Resumed session: the earlier messages are copied, then a new one follows
+    let fork = write_transcript(&dir, "b.jsonl", &[
+        assistant_line("b", "2026-10-18T10:05:00Z", "m1", 100),
+        assistant_line("b", "2026-10-18T10:10:00Z", "m2", 200),
+        assistant_line("b", "2026-10-18T11:00:00Z", "m3", 400),
+    ]);
+
+    let mut index = MessageIndex::default();
+    let (mut a, mut b) = (cached_file("a.jsonl"), cached_file("b.jsonl"));
+    refresh_single_file(&mut a, &original, &mut index);
+    refresh_single_file(&mut b, &fork, &mut index);
+    assert_eq!(output_tokens(&a), 300);
+    assert_eq!(output_tokens(&b), 400);
+
+    // Re-parsing a file (cache_time moved back) doesn't count its own messages twice
+    a.cache_time = chrono::DateTime::<chrono::Utc>::UNIX_EPOCH;
+    refresh_single_file(&mut a, &original, &mut index);
+    assert_eq!(output_tokens(&a), 300);
+}
+
+#[test]
+fn test_archived_hours_keep_claiming_their_messages() {
+    let dir = temp_dir("dedup-archive");
+    let original = write_transcript(&dir, "a.jsonl", &[assistant_line("a", "2026-10-18T10:05:00Z", "m1", 100)]);
+    let fork = write_transcript(&dir, "b.jsonl", &[
+        assistant_line("b", "2026-10-18T10:05:00Z", "m1", 100),
+        assistant_line("b", "2026-10-18T11:00:00Z", "m2", 400),
+    ]);
+
+    let mut a = cached_file("a.jsonl");
+    refresh_single_file(&mut a, &original, &mut MessageIndex::default());
+
+    // The original transcript is deleted; after a rebuild only its archived hours remain
+    let source = ArchiveSource {
+        root: "personal".to_string(),
+        project: "-proj".to_string(),
+        session: "a".to_string(),
+        file: "a.jsonl".to_string(),
+    };
+    let mut restored = cached_file("a.jsonl");
+    for hour in a.per_hour.values() {
+        let line = serde_json::to_string(&ArchiveRecord::Usage { source: source.clone(), hour: Box::new(hour.clone()) }).unwrap();
+        let ArchiveRecord::Usage { hour, .. } = serde_json::from_str(&line).unwrap() else {
+            panic!("usage record expected");
+        };
+        restored.per_hour.insert(hour.hour_start, *hour);
+    }
+
+    let mut index = MessageIndex::build(&cache_with(restored));
+    let mut b = cached_file("b.jsonl");
+    refresh_single_file(&mut b, &fork, &mut index);
+    assert_eq!(output_tokens(&b), 400);
+}
@@ -0,0 +1,85 @@
+mod fixtures;
+
+use chrono::{DateTime, Duration, Utc};
+use rs_claude_bar::analyze::{estimate_limit, model_weight, EstimateSource, LimitEstimate};
+use rs_claude_bar::config::Plan;
+
+use fixtures::now;
+
+fn days_ago(days: i64) -> DateTime<Utc> {
+    now() - Duration::days(days)
+}
+
+#[test]
+fn test_model_weight() {
+    assert_eq!(model_weight("claude-opus-4-1-20250805"), 5.0);
+    assert_eq!(model_weight("claude-sonnet-4-20250514"), 1.0);
+    assert_eq!(model_weight("claude-3-5-haiku-20241022"), 0.27);
+    assert_eq!(model_weight("unknown"), 1.0);
+}
+
+#[test]
+fn test_preset_without_enough_hits() {
+    let estimate = estimate_limit(&[(days_ago(1), 50_000), (days_ago(2), 60_000)], now(), Plan::Max5);
+    assert_eq!(estimate.source, EstimateSource::Preset);
+    assert_eq!(estimate.value, 88_000);
+    assert_eq!((estimate.low, estimate.high), (88_000, 88_000));
+    assert_eq!(estimate.hits, 2);
+}
+
+#[test]
+fn test_no_preset_until_plan_is_set() {
+    // Configs without a plan don't guess one
+    assert_eq!(Plan::default(), Plan::Custom);
+    let estimate = estimate_limit(&[(days_ago(1), 50_000)], now(), Plan::default());
+    assert_eq!((estimate.value, estimate.source), (0, EstimateSource::Preset));
+}
+
+#[test]
+fn test_identical_hits_have_no_spread() {
+    let hits: Vec<_> = (1..=4).map(|d| (days_ago(d), 40_000)).collect();
+    let estimate = estimate_limit(&hits, now(), Plan::Pro);
+    assert_eq!(estimate.source, EstimateSource::Learned);
+    assert_eq!((estimate.value, estimate.low, estimate.high), (40_000, 40_000, 40_000));
+    assert_eq!(estimate.samples, 4);
+}
+
+#[test]
+fn test_recent_hits_weigh_more() {
+    // Limit went up from 20k to 40k a month ago
+    let hits = vec![
+        (days_ago(60), 20_000),
+        (days_ago(50), 20_000),
+        (days_ago(2), 40_000),
+        (days_ago(1), 40_000),
+    ];
+    let estimate = estimate_limit(&hits, now(), Plan::Pro);
+    assert!(estimate.value > 38_000, "value {}", estimate.value);
+    assert!(estimate.low <= estimate.value && estimate.value <= estimate.high);
+}
+
+#[test]
+fn test_outliers_are_trimmed() {
+    // A window hit on another machine shows almost no usage here
+    let hits = vec![
+        (days_ago(1), 500),
+        (days_ago(2), 30_000),
+        (days_ago(3), 31_000),
+        (days_ago(4), 29_000),
+        (days_ago(5), 250_000),
+    ];
+    let estimate = estimate_limit(&hits, now(), Plan::Pro);
+    assert_eq!(estimate.hits, 5);
+    assert_eq!(estimate.samples, 3);
+    assert!((29_000..=31_000).contains(&estimate.value), "value {}", estimate.value);
+}
+
+#[test]
+fn test_sum_of_roots() {
+    let learned = estimate_limit(&[(days_ago(1), 10_000); 3], now(), Plan::Pro);
+    let preset = estimate_limit(&[], now(), Plan::Pro);
+    let total = LimitEstimate::sum(&[learned.clone(), preset]);
+    assert_eq!(total.value, 29_000);
+    assert_eq!(total.source, EstimateSource::Preset);
+    assert_eq!(LimitEstimate::sum(&[learned.clone(), learned]).source, EstimateSource::Learned);
+}
@@ -0,0 +1,73 @@
+//! Fixtures shared by the integration tests
+#![allow(dead_code)]
+
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+
+use chrono::{DateTime, TimeZone, Utc};
+use rs_claude_bar::cache::{CacheInfo, CacheStatus, CachedFile, CachedFolder};
+
+/// Fixed "now" of the tests: Sunday 2026-10-18 18:00 UTC
+pub fn now() -> DateTime<Utc> {
+    utc(2026, 10, 18, 18, 0)
+}
+
+pub fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+}
+
+/// Empty directory under the system temp dir, unique to this test process and `name`
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("claude-bar-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Transcript `name` in `dir` (created if needed) holding `lines`
+pub fn write_transcript(dir: &Path, name: &str, lines: &[String]) -> PathBuf {
+    fs::create_dir_all(dir).unwrap();
+    let path = dir.join(name);
+    fs::write(&path, lines.join("\n")).unwrap();
+    path
+}
+
+/// Output tokens of all hours of a cached file
+pub fn output_tokens(file: &CachedFile) -> u32 {
+    file.per_hour.values().map(|hour| hour.output_tokens).sum()
+}
+
+/// Cache entry of a transcript that was never parsed
+pub fn cached_file(file_name: &str) -> CachedFile {
+    CachedFile {
+        file_name: file_name.to_string(),
+        cache_time: DateTime::<Utc>::UNIX_EPOCH,
+        blocks: HashMap::new(),
+        per_hour: HashMap::new(),
+        session_id: None,
+        missing_since: None,
+        archived_until: None,
+        claude_version: None,
+        parse_errors: Vec::new(),
+        recent_events: Vec::new(),
+        open_tool_calls: HashMap::new(),
+        cache_status: CacheStatus::NotInCache,
+        modified_time: DateTime::<Utc>::UNIX_EPOCH,
+        created_time: DateTime::<Utc>::UNIX_EPOCH,
+        size_bytes: 0,
+    }
+}
+
+/// Cache holding `file` alone, in root "personal" and project "-proj"
+pub fn cache_with(file: CachedFile) -> CacheInfo {
+    let mut cache = CacheInfo::default();
+    cache.roots.entry("personal".to_string()).or_default()
+        .folders.insert("-proj".to_string(), CachedFolder { files: [(file.file_name.clone(), file)].into() });
+    cache
+}
+
+/// Transcript line of an assistant response
+pub fn assistant_line(session: &str, timestamp: &str, message_id: &str, output_tokens: u32) -> String {
+    format!(
+        r#"{{"type":"assistant","sessionId":"{session}","uuid":"{message_id}-{session}","requestId":"req-{message_id}","timestamp":"{timestamp}","message":{{"id":"{message_id}","role":"assistant","model":"claude-sonnet-4-5","content":[{{"type":"text","text":"ok"}}],"usage":{{"input_tokens":10,"output_tokens":{output_tokens}}}}}}}"#
+    )
+}
@@ -0,0 +1,29 @@
+mod fixtures;
+
+use std::time::Duration;
+
+use rs_claude_bar::cache::FileLock;
+
+use fixtures::temp_dir;
+
+#[test]
+fn test_lock_is_exclusive_until_dropped() {
+    let path = temp_dir("lock").join("cache.lock");
+
+    let lock = FileLock::acquire(&path, Duration::from_millis(10)).expect("free lock");
+    assert!(FileLock::acquire(&path, Duration::from_millis(30)).is_none());
+
+    drop(lock);
+    // The lock file is left in place and can be locked again
+    assert!(path.exists());
+    assert!(FileLock::acquire(&path, Duration::from_millis(10)).is_some());
+}
+
+#[test]
+fn test_leftover_lock_file_is_not_held() {
+    let path = temp_dir("lock-left").join("cache.lock");
+    // Lock file of a process that died: nothing holds it anymore
+    std::fs::write(&path, "12345").unwrap();
+
+    assert!(FileLock::acquire(&path, Duration::from_millis(10)).is_some());
+}
@@ -0,0 +1,79 @@
+mod fixtures;
+
+use std::fs;
+
+use rs_claude_bar::{
+    cache::{refresh_cache, set_file_info, CachedFile, CachedRoot, MessageIndex},
+    config::MissingFilePolicy,
+};
+
+use fixtures::{assistant_line, output_tokens, temp_dir, write_transcript};
+
+/// Root with two parsed transcripts: -old/s1.jsonl and -old/s2.jsonl
+fn parsed_root(name: &str, policy: MissingFilePolicy) -> (std::path::PathBuf, CachedRoot) {
+    let dir = temp_dir(name);
+    for session in ["s1", "s2"] {
+        let line = assistant_line(session, "2026-10-18T10:05:00Z", &format!("m-{}", session), 300);
+        write_transcript(&dir.join("projects/-old"), &format!("{}.jsonl", session), &[line]);
+    }
+    let mut cached = CachedRoot::default();
+    set_file_info(&mut cached, dir.to_str().unwrap(), policy);
+    refresh_cache(&mut cached, dir.to_str().unwrap(), &mut MessageIndex::default());
+    (dir, cached)
+}
+
+fn file<'a>(cached: &'a CachedRoot, project: &str, name: &str) -> Option<&'a CachedFile> {
+    cached.folders.get(project)?.files.get(name)
+}
+
+#[test]
+fn test_moved_transcript_keeps_its_history() {
+    let (dir, mut cached) = parsed_root("missing-moved", MissingFilePolicy::Retain);
+    fs::create_dir_all(dir.join("projects/-new")).unwrap();
+    fs::rename(dir.join("projects/-old/s1.jsonl"), dir.join("projects/-new/s1.jsonl")).unwrap();
+
+    set_file_info(&mut cached, dir.to_str().unwrap(), MissingFilePolicy::Retain);
+    assert!(file(&cached, "-old", "s1.jsonl").is_none());
+    let moved = file(&cached, "-new", "s1.jsonl").unwrap();
+    assert_eq!(moved.session_id.as_deref(), Some("s1"));
+    assert_eq!(output_tokens(moved), 300);
+    assert!(moved.missing_since.is_none());
+}
+
+#[test]
+fn test_retain_flags_deleted_transcripts() {
+    let (dir, mut cached) = parsed_root("missing-retain", MissingFilePolicy::Retain);
+    fs::remove_file(dir.join("projects/-old/s1.jsonl")).unwrap();
+
+    set_file_info(&mut cached, dir.to_str().unwrap(), MissingFilePolicy::Retain);
+    let deleted = file(&cached, "-old", "s1.jsonl").unwrap();
+    assert!(deleted.missing_since.is_some());
+    assert_eq!(output_tokens(deleted), 300);
+    assert!(file(&cached, "-old", "s2.jsonl").unwrap().missing_since.is_none());
+}
+
+#[test]
+fn test_prune_drops_deleted_transcripts() {
+    let (dir, mut cached) = parsed_root("missing-prune", MissingFilePolicy::Prune);
+    fs::remove_file(dir.join("projects/-old/s1.jsonl")).unwrap();
+
+    set_file_info(&mut cached, dir.to_str().unwrap(), MissingFilePolicy::Prune);
+    assert!(file(&cached, "-old", "s1.jsonl").is_none());
+    assert!(file(&cached, "-old", "s2.jsonl").is_some());
+}
+
+#[test]
+fn test_unreachable_root_is_left_untouched() {
+    for policy in [MissingFilePolicy::Retain, MissingFilePolicy::Prune] {
+        let (dir, mut cached) = parsed_root("missing-unmounted", policy);
+        // The whole root disappears (unmounted drive)
+        fs::remove_dir_all(&dir).unwrap();
+
+        set_file_info(&mut cached, dir.to_str().unwrap(), policy);
+        for name in ["s1.jsonl", "s2.jsonl"] {
+            let kept = file(&cached, "-old", name).unwrap();
+            assert!(kept.missing_since.is_none());
+            assert_eq!(output_tokens(kept), 300);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+mod fixtures;
+
+use rs_claude_bar::analyze::{parse_drift, AnalyzerQuery};
+use rs_claude_bar::cache::{CacheInfo, CachedFile, PerHourBlock};
+
+use fixtures::{cache_with, cached_file, now};
+
+/// Transcript written by Claude Code `version`, with `lost` of its 100 usage lines unparsed
+fn transcript(file_name: &str, version: &str, lost: u32) -> CachedFile {
+    let mut file = cached_file(file_name);
+    file.claude_version = Some(version.to_string());
+    let mut hour = PerHourBlock::new(now(), now());
+    hour.parse_health.usage_lines = 100 - lost;
+    hour.parse_health.lost_usage_lines = lost;
+    file.per_hour.insert(now(), hour);
+    file
+}
+
+fn cache_of(files: Vec<CachedFile>) -> CacheInfo {
+    let mut files = files.into_iter();
+    let mut cache = cache_with(files.next().unwrap());
+    let folder = cache.roots.get_mut("personal").unwrap().folders.get_mut("-proj").unwrap();
+    for file in files {
+        folder.files.insert(file.file_name.clone(), file);
+    }
+    cache
+}
+
+#[test]
+fn test_drift_after_upgrade() {
+    // 2.0.10 is newer than 2.0.9
+    let cache = cache_of(vec![transcript("a.jsonl", "2.0.9", 1), transcript("b.jsonl", "2.0.10", 20)]);
+    let drift = parse_drift(&cache, &AnalyzerQuery::default(), 5.0).unwrap();
+    assert_eq!(drift.version, "2.0.10");
+    assert_eq!(drift.previous_version.as_deref(), Some("2.0.9"));
+    assert_eq!(drift.lost_usage_percent, 20.0);
+    assert_eq!(drift.previous_lost_usage_percent, 1.0);
+
+    // The threshold is configurable
+    assert!(parse_drift(&cache, &AnalyzerQuery::default(), 25.0).is_none());
+}
+
+#[test]
+fn test_no_drift_without_change() {
+    // Healthy newest version
+    let cache = cache_of(vec![transcript("a.jsonl", "2.0.9", 20), transcript("b.jsonl", "2.0.10", 0)]);
+    assert!(parse_drift(&cache, &AnalyzerQuery::default(), 5.0).is_none());
+
+    // Already as broken before the upgrade
+    let cache = cache_of(vec![transcript("a.jsonl", "2.0.9", 20), transcript("b.jsonl", "2.0.10", 20)]);
+    assert!(parse_drift(&cache, &AnalyzerQuery::default(), 5.0).is_none());
+}
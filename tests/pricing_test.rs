@@ -0,0 +1,28 @@
+use rs_claude_bar::analyze::model_price;
+use rs_claude_bar::cache::ModelUsage;
+
+#[test]
+fn test_model_prices() {
+    assert_eq!(model_price("claude-opus-4-1-20250805").output, 75.0);
+    assert_eq!(model_price("claude-opus-4-20250514").output, 75.0);
+    assert_eq!(model_price("claude-opus-4-5-20251101").output, 25.0);
+    assert_eq!(model_price("claude-3-5-haiku-20241022").output, 4.0);
+    assert_eq!(model_price("claude-haiku-4-5").output, 5.0);
+    assert_eq!(model_price("claude-sonnet-4-5").output, 15.0);
+    // Unknown models are priced as Sonnet
+    assert_eq!(model_price("<synthetic>"), model_price("claude-sonnet-4"));
+}
+
+#[test]
+fn test_usage_cost() {
+    let usage = ModelUsage {
+        input_tokens: 1_000_000,
+        output_tokens: 100_000,
+        cache_creation_tokens: 1_000_000,
+        cache_read_tokens: 10_000_000,
+        messages: 10,
+    };
+    // 3 + 1.5 + 3.75 + 3
+    let cost = model_price("claude-sonnet-4-5").cost(&usage);
+    assert!((cost - 11.25).abs() < 1e-9, "cost {}", cost);
+}
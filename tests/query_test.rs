@@ -0,0 +1,90 @@
+mod fixtures;
+
+use chrono::Duration;
+use chrono_tz::Tz;
+use rs_claude_bar::analyze::{analyze_period, glob_match, parse_time_bound, AnalyzerQuery};
+use rs_claude_bar::cache::PerHourBlock;
+
+use fixtures::{cache_with, cached_file, now, utc};
+
+#[test]
+fn test_relative_bounds() {
+    assert_eq!(parse_time_bound("7d", now(), Tz::UTC, false), Ok(now() - Duration::days(7)));
+    assert_eq!(parse_time_bound("12h", now(), Tz::UTC, false), Ok(now() - Duration::hours(12)));
+    assert_eq!(parse_time_bound("2w", now(), Tz::UTC, false), Ok(now() - Duration::weeks(2)));
+}
+
+#[test]
+fn test_date_bounds_in_zone() {
+    let paris: Tz = "Europe/Paris".parse().unwrap();
+    // Midnight in Paris is 22:00 UTC the day before (summer time)
+    assert_eq!(
+        parse_time_bound("2026-10-01", now(), paris, false),
+        Ok(utc(2026, 9, 30, 22, 0))
+    );
+    // --until includes the whole day
+    assert_eq!(
+        parse_time_bound("2026-10-01", now(), paris, true),
+        Ok(utc(2026, 10, 1, 22, 0))
+    );
+    assert_eq!(
+        parse_time_bound("2026-10-01T14:30", now(), paris, false),
+        Ok(utc(2026, 10, 1, 12, 30))
+    );
+    assert_eq!(
+        parse_time_bound("2026-10-01T14:30:00Z", now(), paris, false),
+        Ok(utc(2026, 10, 1, 14, 30))
+    );
+    assert!(parse_time_bound("last tuesday", now(), paris, false).is_err());
+}
+
+#[test]
+fn test_project_patterns() {
+    assert!(glob_match("*my-app*", "-home-bob-my-app-web"));
+    assert!(glob_match("-home-?ob-*", "-home-bob-my-app"));
+    assert!(!glob_match("*my-app", "-home-bob-my-app-web"));
+
+    let query = AnalyzerQuery { projects: vec!["my-app".to_string(), "*tools".to_string()], ..Default::default() };
+    assert!(query.matches_project("-home-bob-my-app-web"));
+    assert!(query.matches_project("-home-bob-dev-tools"));
+    assert!(!query.matches_project("-home-bob-website"));
+}
+
+#[test]
+fn test_model_and_hour_matching() {
+    let query = AnalyzerQuery {
+        since: Some(now() - Duration::minutes(100)),
+        models: vec!["Opus".to_string()],
+        ..Default::default()
+    };
+    assert!(query.matches_model("claude-opus-4-1-20250805"));
+    assert!(!query.matches_model("claude-sonnet-4-5"));
+    // The hour the period starts in counts, the one before doesn't
+    let hour = utc(2026, 10, 18, 16, 0);
+    assert!(query.matches_hour(hour + Duration::hours(1)));
+    assert!(query.matches_hour(hour));
+    assert!(!query.matches_hour(hour - Duration::hours(1)));
+}
+
+#[test]
+fn test_adjacent_periods_share_no_hour() {
+    let mut file = cached_file("s1.jsonl");
+    for (hour, output_tokens) in [(13, 100), (14, 200), (15, 400)] {
+        let hour_start = utc(2026, 10, 18, hour, 0);
+        let mut block = PerHourBlock::new(hour_start, hour_start);
+        block.output_tokens = output_tokens;
+        file.per_hour.insert(hour_start, block);
+    }
+    let cache = cache_with(file);
+    let query = AnalyzerQuery::default();
+
+    // 14:00-15:00 starts before the split, so only the earlier period counts it
+    let split = utc(2026, 10, 18, 14, 30);
+    let earlier = analyze_period(&cache, &query, utc(2026, 10, 18, 12, 0), split);
+    let later = analyze_period(&cache, &query, split, utc(2026, 10, 18, 17, 0));
+    assert_eq!(earlier.stats.output_tokens, 300);
+    assert_eq!(later.stats.output_tokens, 400);
+    // The reported bounds stay the requested ones
+    assert_eq!(earlier.end, split);
+    assert_eq!(later.start, split);
+}
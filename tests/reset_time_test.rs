@@ -0,0 +1,135 @@
+mod fixtures;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use rs_claude_bar::cache::{extract_reset_time_text, parse_reset_time, parse_reset_time_in, resolve_reset_text, LimitKind};
+
+use fixtures::utc;
+
+#[test]
+fn test_reset_time_corpus() {
+    // Blocked at 2025-08-22 16:43 UTC (18:43 in Paris, 12:43 in New York)
+    let blocked = utc(2025, 8, 22, 16, 43);
+
+    let cases: Vec<(&str, Option<DateTime<Utc>>)> = vec![
+        ("5-hour limit reached ∙ resets 5pm", Some(utc(2025, 8, 22, 17, 0))),
+        ("5-hour limit reached ∙ resets 4pm", Some(utc(2025, 8, 23, 16, 0))),
+        ("5-hour limit reached ∙ resets 12am", Some(utc(2025, 8, 23, 0, 0))),
+        ("5-hour limit reached ∙ resets 12pm", Some(utc(2025, 8, 23, 12, 0))),
+        ("5-hour limit reached ∙ resets 5:30pm", Some(utc(2025, 8, 22, 17, 30))),
+        ("5-hour limit reached ∙ resets 5:30 PM", Some(utc(2025, 8, 22, 17, 30))),
+        ("5-hour limit reached ∙ resets 17:00", Some(utc(2025, 8, 22, 17, 0))),
+        ("5-hour limit reached ∙ resets 9pm (Europe/Paris)", Some(utc(2025, 8, 22, 19, 0))),
+        ("5-hour limit reached ∙ resets 6pm (Europe/Paris)", Some(utc(2025, 8, 23, 16, 0))),
+        ("5-hour limit reached ∙ resets 2pm (America/New_York)", Some(utc(2025, 8, 22, 18, 0))),
+        ("5-hour limit reached ∙ resets 5pm (UTC)", Some(utc(2025, 8, 22, 17, 0))),
+        ("Session limit reached ∙ resets in 2h30m", Some(utc(2025, 8, 22, 19, 13))),
+        ("Session limit reached ∙ resets in 45 minutes", Some(utc(2025, 8, 22, 17, 28))),
+        ("Session limit reached ∙ resets in 3 hours", Some(utc(2025, 8, 22, 19, 43))),
+        ("Weekly limit reached ∙ resets Aug 25, 9am", Some(utc(2025, 8, 25, 9, 0))),
+        ("Weekly limit reached ∙ resets Aug 25, 9am (Europe/Paris)", Some(utc(2025, 8, 25, 7, 0))),
+        ("Weekly limit reached ∙ resets Oct 20, 2025 at 9:15am", Some(utc(2025, 10, 20, 9, 15))),
+        ("Weekly limit reached ∙ resets September 1st", Some(utc(2025, 9, 1, 0, 0))),
+        ("Opus weekly limit reached ∙ resets Sep 1, 9am · /upgrade to keep using Claude", Some(utc(2025, 9, 1, 9, 0))),
+        ("weekly limit reached ∙ resets Mon 9am", Some(utc(2025, 8, 25, 9, 0))),
+        ("Opus weekly limit reached ∙ resets Monday, 9:30am (Europe/Paris)", Some(utc(2025, 8, 25, 7, 30))),
+        ("weekly limit reached ∙ resets Fri 5pm", Some(utc(2025, 8, 22, 17, 0))),
+        ("weekly limit reached ∙ resets Fri 4pm", Some(utc(2025, 8, 29, 16, 0))),
+        ("Claude AI usage limit reached|1755882000", Some(utc(2025, 8, 22, 17, 0))),
+        ("5-hour limit reached ∙ resets 5pm (Mars/Olympus)", None),
+        ("5-hour limit reached ∙ resets soon", None),
+        ("API Error: 529 Overloaded", None),
+    ];
+
+    for (message, expected) in cases {
+        assert_eq!(parse_reset_time(message, blocked), expected, "message: {message}");
+    }
+}
+
+#[test]
+fn test_reset_date_rolls_into_next_year() {
+    let blocked = utc(2025, 12, 30, 10, 0);
+    assert_eq!(
+        parse_reset_time("Weekly limit reached ∙ resets Jan 2, 9am", blocked),
+        Some(utc(2026, 1, 2, 9, 0))
+    );
+}
+
+#[test]
+fn test_reset_across_dst_change() {
+    // Paris switches from CEST (+2) to CET (+1) on 2025-10-26
+    let blocked = utc(2025, 10, 25, 20, 0);
+    assert_eq!(
+        parse_reset_time("5-hour limit reached ∙ resets 4am (Europe/Paris)", blocked),
+        Some(utc(2025, 10, 26, 3, 0))
+    );
+    assert_eq!(
+        parse_reset_time("Weekly limit reached ∙ resets Oct 27, 9am (Europe/Paris)", blocked),
+        Some(utc(2025, 10, 27, 8, 0))
+    );
+}
+
+#[test]
+fn test_reset_time_in_default_zone() {
+    // Blocked at 18:43 in Paris: "5pm" without zone is read in the user's zone
+    let blocked = utc(2025, 8, 22, 16, 43);
+    let paris: Tz = "Europe/Paris".parse().unwrap();
+    assert_eq!(
+        parse_reset_time_in("5-hour limit reached ∙ resets 9pm", blocked, paris),
+        Some(utc(2025, 8, 22, 19, 0))
+    );
+    // An explicit zone still wins
+    assert_eq!(
+        parse_reset_time_in("5-hour limit reached ∙ resets 5pm (UTC)", blocked, paris),
+        Some(utc(2025, 8, 22, 17, 0))
+    );
+    assert_eq!(
+        parse_reset_time_in("Session limit reached ∙ resets in 45 minutes", blocked, paris),
+        Some(utc(2025, 8, 22, 17, 28))
+    );
+}
+
+#[test]
+fn test_reset_time_text() {
+    assert_eq!(extract_reset_time_text("5-hour limit reached ∙ resets 5pm"), "5pm");
+    assert_eq!(
+        extract_reset_time_text("5-hour limit reached ∙ resets 5:30pm (Europe/Paris)"),
+        "5:30pm (Europe/Paris)"
+    );
+    assert_eq!(
+        extract_reset_time_text("Weekly limit reached ∙ resets Oct 20, 9am · /upgrade to increase."),
+        "Oct 20, 9am"
+    );
+    assert_eq!(extract_reset_time_text("Claude AI usage limit reached|1755882000"), "1755882000");
+    assert_eq!(extract_reset_time_text("API Error: 529 Overloaded"), "unknown");
+}
+
+#[test]
+fn test_resolve_reset_text() {
+    // The cached phrase resolves like the message, in whichever zone is configured when read
+    let blocked = utc(2025, 8, 22, 16, 43);
+    let paris: Tz = "Europe/Paris".parse().unwrap();
+    for message in [
+        "5-hour limit reached ∙ resets 9pm",
+        "5-hour limit reached ∙ resets 5pm (UTC)",
+        "Session limit reached ∙ resets in 2h30m",
+        "Weekly limit reached ∙ resets Oct 20, 2025 at 9:15am",
+        "Claude AI usage limit reached|1755882000",
+        "API Error: 529 Overloaded",
+    ] {
+        let text = extract_reset_time_text(message);
+        for zone in [Tz::UTC, paris] {
+            assert_eq!(resolve_reset_text(&text, blocked, zone), parse_reset_time_in(message, blocked, zone), "message: {message}");
+        }
+    }
+    assert_eq!(resolve_reset_text("9pm", blocked, Tz::UTC), Some(utc(2025, 8, 22, 21, 0)));
+    assert_eq!(resolve_reset_text("9pm", blocked, paris), Some(utc(2025, 8, 22, 19, 0)));
+}
+
+#[test]
+fn test_limit_kind() {
+    assert_eq!(LimitKind::from_message("5-hour limit reached ∙ resets 5pm"), LimitKind::FiveHour);
+    assert_eq!(LimitKind::from_message("Claude AI usage limit reached|1755882000"), LimitKind::FiveHour);
+    assert_eq!(LimitKind::from_message("Weekly limit reached ∙ resets Mon 9am"), LimitKind::Weekly);
+    assert_eq!(LimitKind::from_message("Opus weekly limit reached ∙ resets Mon 9am"), LimitKind::WeeklyOpus);
+}
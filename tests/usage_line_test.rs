@@ -0,0 +1,45 @@
+mod fixtures;
+
+use std::fs;
+
+use rs_claude_bar::{cache::parse_transcript_since, claude_types::usage_line::UsageLine};
+
+use fixtures::{assistant_line, temp_dir, utc};
+
+/// Full line as Claude Code writes it
+const FULL_LINE: &str = r#"{"parentUuid":null,"isSidechain":false,"userType":"external","cwd":"/home/me/app","sessionId":"s1","version":"2.0.14","gitBranch":"main","type":"assistant","uuid":"u1","timestamp":"2026-10-18T10:05:00Z","requestId":"r1","message":{"id":"m1","role":"assistant","model":"claude-sonnet-4-5","content":[{"type":"text","text":"ok"}],"usage":{"input_tokens":10,"output_tokens":100}}}"#;
+
+#[test]
+fn test_usage_kept_without_optional_fields() {
+    // No cwd, gitBranch, version, uuid nor userType
+    let line: UsageLine = serde_json::from_str(&assistant_line("s1", "2026-10-18T10:05:00Z", "m1", 100)).unwrap();
+    assert!(line.is_known());
+    assert!(line.version.is_none());
+    assert_eq!(line.usage().map(|usage| usage.output_tokens), Some(100));
+    assert_eq!(line.timestamp_utc(), Some(utc(2026, 10, 18, 10, 5)));
+
+    // Only a type, a timestamp and a message
+    let line: UsageLine = serde_json::from_str(
+        r#"{"type":"assistant","timestamp":"2026-10-18T10:06:00Z","message":{"usage":{"input_tokens":1,"output_tokens":7}}}"#,
+    ).unwrap();
+    assert_eq!(line.usage().map(|usage| usage.output_tokens), Some(7));
+}
+
+#[test]
+fn test_transcript_with_partial_lines() {
+    let path = temp_dir("usage-line").join("s1.jsonl");
+    let lines = [
+        FULL_LINE.to_string(),
+        assistant_line("s1", "2026-10-18T10:10:00Z", "m2", 200),
+        r#"{"type":"assistant","timestamp":"2026-10-18T10:15:00Z","message":{"id":"m3","usage":{"input_tokens":1,"output_tokens":300}}}"#.to_string(),
+    ];
+    fs::write(&path, lines.join("\n")).unwrap();
+
+    let parsed = parse_transcript_since(path.to_str().unwrap(), utc(2026, 10, 18, 0, 0));
+    let output: u32 = parsed.entries.iter().filter_map(|entry| entry.usage()).map(|usage| usage.output_tokens).sum();
+    assert_eq!(output, 600);
+    let health = &parsed.health[&utc(2026, 10, 18, 10, 0)];
+    assert_eq!((health.usage_lines, health.lost_usage_lines), (3, 0));
+    // Newest line that has one
+    assert_eq!(parsed.version.as_deref(), Some("2.0.14"));
+}
@@ -0,0 +1,41 @@
+mod fixtures;
+
+use chrono::Duration;
+use rs_claude_bar::analyze::active_window_start;
+use rs_claude_bar::cache::UsageEvent;
+
+use fixtures::now;
+
+fn events(minutes_ago: &[i64]) -> Vec<UsageEvent> {
+    minutes_ago.iter()
+        .map(|ago| UsageEvent { timestamp: now() - Duration::minutes(*ago), output_tokens: 100, model: None })
+        .collect()
+}
+
+#[test]
+fn test_window_starts_at_first_message() {
+    let events = events(&[95, 60, 2]);
+    assert_eq!(active_window_start(&events, None, now()), Some(now() - Duration::minutes(95)));
+}
+
+#[test]
+fn test_window_chained_after_expiry() {
+    // The window started 5h50 ago expired 50 minutes ago
+    let events = events(&[350, 300, 40, 10]);
+    assert_eq!(active_window_start(&events, None, now()), Some(now() - Duration::minutes(40)));
+}
+
+#[test]
+fn test_no_window_when_idle() {
+    let events = events(&[340, 330]);
+    assert_eq!(active_window_start(&events, None, now()), None);
+    assert_eq!(active_window_start(&[], None, now()), None);
+}
+
+#[test]
+fn test_window_after_limit_reset() {
+    // Usage locked until 30 minutes ago: the next window starts with the first message after it
+    let events = events(&[120, 90, 25, 5]);
+    let unlock = now() - Duration::minutes(30);
+    assert_eq!(active_window_start(&events, Some(unlock), now()), Some(now() - Duration::minutes(25)));
+}